@@ -0,0 +1,3 @@
+-- Migration Down: Drop the jokes.lang index
+
+DROP INDEX IF EXISTS idx_jokes_lang;
@@ -0,0 +1,3 @@
+-- Migration Up: Index jokes.lang so language filters can use an index like the other filter columns
+
+CREATE INDEX idx_jokes_lang ON jokes(lang);
@@ -0,0 +1,4 @@
+-- Migration Down: Drop keyset pagination support
+
+DROP INDEX IF EXISTS idx_jokes_created_at_id;
+ALTER TABLE jokes ALTER COLUMN created_at DROP NOT NULL;
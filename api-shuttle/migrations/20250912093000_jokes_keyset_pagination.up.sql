@@ -0,0 +1,8 @@
+-- Migration Up: Support keyset pagination over (created_at, id)
+
+-- Keyset comparisons skip NULLs, so every row needs a creation time
+UPDATE jokes SET created_at = COALESCE(updated_at, CURRENT_TIMESTAMP) WHERE created_at IS NULL;
+ALTER TABLE jokes ALTER COLUMN created_at SET NOT NULL;
+
+-- Composite index matching the listing sort order
+CREATE INDEX idx_jokes_created_at_id ON jokes(created_at, id);
@@ -0,0 +1,4 @@
+-- Migration Down: Drop full-text search support
+
+DROP INDEX IF EXISTS idx_jokes_search_vector;
+ALTER TABLE jokes DROP COLUMN IF EXISTS search_vector;
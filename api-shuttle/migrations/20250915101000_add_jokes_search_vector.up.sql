@@ -0,0 +1,14 @@
+-- Migration Up: Full-text search over joke content
+
+-- Generated tsvector over the text fields of both joke shapes
+ALTER TABLE jokes ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+    to_tsvector(
+        'english',
+        COALESCE(joke->>'content', '') || ' ' ||
+        COALESCE(joke->>'setup', '') || ' ' ||
+        COALESCE(joke->>'punchline', '')
+    )
+) STORED;
+
+-- Create GIN index for text search queries
+CREATE INDEX idx_jokes_search_vector ON jokes USING gin (search_vector);
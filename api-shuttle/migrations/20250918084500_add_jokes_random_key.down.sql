@@ -0,0 +1,4 @@
+-- Migration Down: Drop the random key
+
+DROP INDEX IF EXISTS idx_jokes_random_key;
+ALTER TABLE jokes DROP COLUMN IF EXISTS random_key;
@@ -0,0 +1,6 @@
+-- Migration Up: Indexed random key for random selection without sorting the whole table
+
+-- random() is volatile, so every existing row gets its own value
+ALTER TABLE jokes ADD COLUMN random_key DOUBLE PRECISION NOT NULL DEFAULT random();
+
+CREATE INDEX idx_jokes_random_key ON jokes(random_key);
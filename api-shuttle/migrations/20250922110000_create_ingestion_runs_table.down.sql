@@ -0,0 +1,3 @@
+-- Migration Down: Drop ingestion_runs table
+
+DROP TABLE IF EXISTS ingestion_runs;
@@ -0,0 +1,16 @@
+-- Migration Up: Create ingestion_runs table recording background ingestion history
+
+CREATE TABLE ingestion_runs (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    provider VARCHAR(255) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'running' CHECK (status IN ('running', 'succeeded', 'failed')),
+    requested_count INTEGER NOT NULL,
+    fetched_count INTEGER,
+    saved_count INTEGER,
+    error TEXT,
+    started_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    finished_at TIMESTAMP WITH TIME ZONE
+);
+
+-- Latest runs per provider
+CREATE INDEX idx_ingestion_runs_provider_started_at ON ingestion_runs(provider, started_at DESC);
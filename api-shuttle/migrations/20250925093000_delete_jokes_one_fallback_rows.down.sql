@@ -0,0 +1,3 @@
+-- Migration Down: Nothing to restore, the deleted rows were duplicates of a placeholder joke
+
+SELECT 1;
@@ -0,0 +1,7 @@
+-- Migration Up: Delete the hardcoded fallback joke JokesOneProvider used to return on failure
+-- These rows have no external_id, so every failed call inserted another copy
+
+DELETE FROM jokes
+WHERE provider = 'https://api.jokes.one'
+  AND external_id IS NULL
+  AND joke->>'content' = 'Why don''t scientists trust atoms? Because they make up everything!';
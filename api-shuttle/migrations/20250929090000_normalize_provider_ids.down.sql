@@ -0,0 +1,20 @@
+-- Migration Down: Restore provider base URLs in the provider columns
+
+CREATE TEMPORARY TABLE provider_ids (base_url VARCHAR(255) PRIMARY KEY, id VARCHAR(255) NOT NULL);
+INSERT INTO provider_ids (base_url, id) VALUES
+    ('https://v2.jokeapi.dev', 'jokeapi'),
+    ('https://icanhazdadjoke.com', 'icanhazdadjoke'),
+    ('https://api.chucknorris.io', 'chucknorris'),
+    ('https://official-joke-api.appspot.com', 'officialjoke'),
+    ('https://sv443.net/jokeapi/v2', 'sv443'),
+    ('https://api.jokes.one', 'jokesone');
+
+UPDATE jokes SET provider = provider_ids.base_url
+FROM provider_ids
+WHERE jokes.provider = provider_ids.id;
+
+UPDATE ingestion_runs SET provider = provider_ids.base_url
+FROM provider_ids
+WHERE ingestion_runs.provider = provider_ids.id;
+
+DROP TABLE provider_ids;
@@ -0,0 +1,4 @@
+-- Migration Down: Restore provider categories into the category column
+
+UPDATE jokes SET category = provider_category;
+ALTER TABLE jokes DROP COLUMN provider_category;
@@ -0,0 +1,39 @@
+-- Migration Up: Store canonical categories, keeping the provider's own category alongside
+
+ALTER TABLE jokes ADD COLUMN provider_category VARCHAR(100);
+UPDATE jokes SET provider_category = category, category = LOWER(category);
+
+-- Provider categories whose canonical name differs, mirroring categories::CATEGORY_MAPPINGS
+CREATE TEMPORARY TABLE category_mappings (
+    provider VARCHAR(255) NOT NULL,
+    provider_category VARCHAR(100) NOT NULL,
+    category VARCHAR(100) NOT NULL,
+    PRIMARY KEY (provider, provider_category)
+);
+INSERT INTO category_mappings (provider, provider_category, category) VALUES
+    ('jokeapi', 'misc', 'general'),
+    ('jokeapi', 'miscellaneous', 'general'),
+    ('sv443', 'misc', 'general'),
+    ('sv443', 'miscellaneous', 'general'),
+    ('icanhazdadjoke', 'dad jokes', 'dad'),
+    ('chucknorris', 'dev', 'programming'),
+    ('chucknorris', 'movie', 'movies'),
+    ('chucknorris', 'political', 'politics'),
+    ('chucknorris', 'sport', 'sports'),
+    ('chucknorris', 'uncategorized', 'general');
+
+UPDATE jokes SET category = category_mappings.category
+FROM category_mappings
+WHERE jokes.provider = category_mappings.provider
+    AND jokes.category = category_mappings.provider_category;
+
+-- Anything left outside the taxonomy is filed under the default category
+UPDATE jokes SET category = 'general'
+WHERE category IS NOT NULL
+    AND category NOT IN (
+        'animal', 'career', 'celebrity', 'christmas', 'dad', 'dark', 'explicit', 'fashion',
+        'food', 'general', 'history', 'knock-knock', 'money', 'movies', 'music', 'politics',
+        'programming', 'pun', 'religion', 'science', 'spooky', 'sports', 'travel'
+    );
+
+DROP TABLE category_mappings;
@@ -0,0 +1,3 @@
+-- Migration Down: Drop the category random key index
+
+DROP INDEX IF EXISTS idx_jokes_category_random_key;
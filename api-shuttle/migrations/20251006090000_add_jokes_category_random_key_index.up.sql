@@ -0,0 +1,6 @@
+-- Migration Up: Composite index so random picks within a category stay index scans
+
+-- Category is the selective filter (the cache fallback and GET /jokes/random?category= use it);
+-- type, safe, lang and provider match large shares of the table, so scanning
+-- idx_jokes_random_key and filtering those rows out stays cheap without more indexes
+CREATE INDEX idx_jokes_category_random_key ON jokes(category, random_key);
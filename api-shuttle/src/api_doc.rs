@@ -11,20 +11,50 @@ use utoipa::OpenApi;
     crate::routes::root::hello,
     crate::routes::jokes::retrieve::retrieve_jokes,
     crate::routes::jokes::random::random_joke,
+    crate::routes::jokes::fresh::fresh_joke,
+    crate::routes::jokes::list::list_jokes,
+    crate::routes::jokes::search::search_jokes,
+    crate::routes::jokes::detail::get_joke,
+    crate::routes::providers::list::list_providers,
+    crate::routes::providers::health::provider_health,
+    crate::routes::categories::list::list_categories,
   ),
   components(
     schemas(
       crate::routes::jokes::retrieve::RetrieveJokesParams,
       crate::routes::jokes::retrieve::JokeResponse,
       crate::routes::jokes::retrieve::JokeSummary,
+      crate::routes::jokes::retrieve::SkippedJoke,
+      crate::routes::jokes::retrieve::FailedCall,
+      crate::routes::jokes::retrieve::ProviderHarvest,
+      crate::routes::jokes::random::RandomJokeParams,
+      crate::routes::jokes::random::JokeFilterParams,
       crate::routes::jokes::random::RandomJokeResponse,
       crate::routes::jokes::random::JokeDetail,
       crate::routes::jokes::random::JokeContent,
+      crate::routes::jokes::fresh::FreshJokeParams,
+      crate::routes::jokes::fresh::FreshJokeResponse,
+      crate::routes::jokes::fresh::FreshJoke,
+      crate::routes::jokes::fresh::FallbackDetail,
+      crate::routes::jokes::list::ListJokesParams,
+      crate::routes::jokes::list::ListJokesResponse,
+      crate::routes::jokes::search::SearchJokesParams,
+      crate::routes::jokes::search::SearchJokesResponse,
+      crate::routes::jokes::search::SearchResult,
+      crate::routes::providers::health::ProvidersHealthResponse,
+      crate::routes::providers::health::ProviderHealthStatus,
+      crate::routes::providers::health::FallbackUsage,
+      crate::routes::providers::list::ProvidersResponse,
+      crate::routes::providers::list::ProviderSummary,
+      crate::routes::categories::list::CategoriesResponse,
+      crate::routes::categories::list::CategorySummary,
     )
   ),
   tags(
     (name = "root", description = "Root endpoint"),
-    (name = "jokes", description = "Joke retrieval and management endpoints")
+    (name = "jokes", description = "Joke retrieval and management endpoints"),
+    (name = "providers", description = "Joke provider status endpoints"),
+    (name = "categories", description = "Canonical joke category endpoints")
   )
 )]
 pub struct ApiDoc;
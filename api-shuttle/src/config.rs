@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use shuttle_runtime::SecretStore;
+use agitated_chebyshev::lib::providers::{
+    CircuitBreakerConfig, FallbackPolicy, HttpClientConfig, ProvidersConfig, RetryPolicy, SelectionStrategy,
+    PROVIDER_IDS,
+};
+
+/// Read the provider retry policy from `PROVIDER_RETRY_MAX_ATTEMPTS`,
+/// `PROVIDER_RETRY_BASE_DELAY_MS` and `PROVIDER_RETRY_MAX_DELAY_MS`
+pub fn retry_policy(secrets: &SecretStore) -> RetryPolicy {
+    let defaults = RetryPolicy::default();
+    let lookup = |key: &str| secrets.get(key).and_then(|value| value.parse::<u64>().ok());
+
+    RetryPolicy {
+        max_attempts: lookup("PROVIDER_RETRY_MAX_ATTEMPTS")
+            .map(|attempts| attempts.max(1) as u32)
+            .unwrap_or(defaults.max_attempts),
+        base_delay: lookup("PROVIDER_RETRY_BASE_DELAY_MS")
+            .map(Duration::from_millis)
+            .unwrap_or(defaults.base_delay),
+        max_delay: lookup("PROVIDER_RETRY_MAX_DELAY_MS")
+            .map(Duration::from_millis)
+            .unwrap_or(defaults.max_delay),
+    }
+}
+
+/// Read the settings of the HTTP client shared by all providers from
+/// `HTTP_CONNECT_TIMEOUT_SECS`, `HTTP_READ_TIMEOUT_SECS`, `HTTP_TIMEOUT_SECS`,
+/// `HTTP_USER_AGENT`, `HTTP_PROXY_URL` and `HTTP_GZIP`
+pub fn http_client_config(secrets: &SecretStore) -> HttpClientConfig {
+    let defaults = HttpClientConfig::default();
+    let secs = |key: &str| secrets.get(key)
+        .and_then(|value| value.parse::<u64>().ok())
+        .map(Duration::from_secs);
+
+    HttpClientConfig {
+        connect_timeout: secs("HTTP_CONNECT_TIMEOUT_SECS").unwrap_or(defaults.connect_timeout),
+        read_timeout: secs("HTTP_READ_TIMEOUT_SECS").unwrap_or(defaults.read_timeout),
+        timeout: secs("HTTP_TIMEOUT_SECS").unwrap_or(defaults.timeout),
+        user_agent: secrets.get("HTTP_USER_AGENT").unwrap_or(defaults.user_agent),
+        proxy: secrets.get("HTTP_PROXY_URL").or(defaults.proxy),
+        gzip: secrets.get("HTTP_GZIP").map(|value| value == "true").unwrap_or(defaults.gzip),
+    }
+}
+
+/// Read the per-provider circuit breaker settings from `CIRCUIT_BREAKER_WINDOW_SIZE`,
+/// `CIRCUIT_BREAKER_MIN_CALLS`, `CIRCUIT_BREAKER_FAILURE_RATE` and `CIRCUIT_BREAKER_OPEN_SECS`
+pub fn circuit_breaker_config(secrets: &SecretStore) -> CircuitBreakerConfig {
+    let defaults = CircuitBreakerConfig::default();
+
+    CircuitBreakerConfig {
+        window_size: secrets.get("CIRCUIT_BREAKER_WINDOW_SIZE")
+            .and_then(|value| value.parse::<usize>().ok())
+            .map(|size| size.max(1))
+            .unwrap_or(defaults.window_size),
+        min_calls: secrets.get("CIRCUIT_BREAKER_MIN_CALLS")
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(defaults.min_calls),
+        failure_rate_threshold: secrets.get("CIRCUIT_BREAKER_FAILURE_RATE")
+            .and_then(|value| value.parse::<f64>().ok())
+            .map(|rate| rate.clamp(0.0, 1.0))
+            .unwrap_or(defaults.failure_rate_threshold),
+        open_duration: secrets.get("CIRCUIT_BREAKER_OPEN_SECS")
+            .and_then(|value| value.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(defaults.open_duration),
+    }
+}
+
+/// Read the provider settings from the TOML file named by `PROVIDERS_CONFIG` (if any),
+/// then apply `PROVIDER_<ID>_<SETTING>` secrets on top, where `<ID>` is the upper-cased
+/// provider id, e.g. `PROVIDER_JOKESONE_API_KEY` or `PROVIDER_CHUCKNORRIS_BASE_URL`. Settings are `ENABLED`, `BASE_URL`, `API_KEY`,
+/// `TIMEOUT_SECS`, `USER_AGENT` and `WEIGHT`.
+pub fn providers_config(secrets: &SecretStore) -> Result<ProvidersConfig, String> {
+    let mut config = match secrets.get("PROVIDERS_CONFIG") {
+        Some(path) => {
+            let contents = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read providers config {}: {}", path, e))?;
+            ProvidersConfig::from_toml(&contents)?
+        }
+        None => ProvidersConfig::default(),
+    };
+
+    for id in PROVIDER_IDS {
+        let lookup = |setting: &str| {
+            let key = format!("PROVIDER_{}_{}", id.to_uppercase(), setting);
+            secrets.get(&key).map(|value| (key, value))
+        };
+        let parse = |setting: &str| -> Result<Option<u64>, String> {
+            lookup(setting)
+                .map(|(key, value)| value.parse().map_err(|_| format!("Invalid {}: {}", key, value)))
+                .transpose()
+        };
+
+        let entry = config.providers.entry(id.to_string()).or_default();
+        if let Some((_, value)) = lookup("ENABLED") {
+            entry.enabled = Some(value == "true");
+        }
+        if let Some((_, value)) = lookup("BASE_URL") {
+            entry.base_url = Some(value);
+        }
+        if let Some((_, value)) = lookup("API_KEY") {
+            entry.api_key = Some(value);
+        }
+        if let Some(timeout_secs) = parse("TIMEOUT_SECS")? {
+            entry.timeout_secs = Some(timeout_secs);
+        }
+        if let Some((_, value)) = lookup("USER_AGENT") {
+            entry.user_agent = Some(value);
+        }
+        if let Some(weight) = parse("WEIGHT")? {
+            entry.weight = Some(u32::try_from(weight).map_err(|_| format!("Weight for {} is too large", id))?);
+        }
+    }
+
+    Ok(config)
+}
+
+/// Read the provider selection strategy from `PROVIDER_SELECTION_STRATEGY` (default: random).
+/// Weighted selection uses the weights from the providers config.
+pub fn selection_strategy(secrets: &SecretStore, weights: &HashMap<String, u32>) -> Result<SelectionStrategy, String> {
+    match secrets.get("PROVIDER_SELECTION_STRATEGY") {
+        Some(name) => SelectionStrategy::parse(&name, weights.clone()),
+        None => Ok(SelectionStrategy::default()),
+    }
+}
+
+/// Read the fallback policy from `PROVIDER_FALLBACK`, a comma-separated list of
+/// 'other_provider', 'cache' and 'any_category' tried in order (default: 'fail')
+pub fn fallback_policy(secrets: &SecretStore) -> Result<FallbackPolicy, String> {
+    match secrets.get("PROVIDER_FALLBACK") {
+        Some(value) => FallbackPolicy::parse(&value),
+        None => Ok(FallbackPolicy::fail()),
+    }
+}
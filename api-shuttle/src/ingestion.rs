@@ -0,0 +1,137 @@
+use std::time::Duration;
+use sqlx::types::Uuid;
+use shuttle_runtime::SecretStore;
+use tokio::time::MissedTickBehavior;
+use agitated_chebyshev::db;
+use agitated_chebyshev::lib::providers::manager::JokeManager;
+
+/// Default time between ingestion runs for a provider
+const DEFAULT_INTERVAL_SECS: u64 = 900;
+/// Default number of jokes requested per ingestion run
+const DEFAULT_BATCH_SIZE: usize = 10;
+
+/// How often and how much to ingest from one provider
+#[derive(Debug, Clone)]
+pub struct ProviderSchedule {
+    pub enabled: bool,
+    pub interval: Duration,
+    pub batch_size: usize,
+}
+
+impl ProviderSchedule {
+    /// Read the schedule for `provider_id` from secrets.
+    ///
+    /// Global defaults come from `INGESTION_ENABLED`, `INGESTION_INTERVAL_SECS` and
+    /// `INGESTION_BATCH_SIZE`; each can be overridden per provider with
+    /// `INGESTION_<PROVIDER>_ENABLED` etc., where `<PROVIDER>` is the upper-cased
+    /// provider id (e.g. `CHUCKNORRIS`).
+    pub fn from_secrets(secrets: &SecretStore, provider_id: &str) -> Self {
+        let key = provider_id.to_uppercase();
+        let lookup = |setting: &str| {
+            secrets
+                .get(&format!("INGESTION_{}_{}", key, setting))
+                .or_else(|| secrets.get(&format!("INGESTION_{}", setting)))
+        };
+
+        Self {
+            enabled: lookup("ENABLED").map(|v| v == "true").unwrap_or(true),
+            interval: Duration::from_secs(
+                lookup("INTERVAL_SECS")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(DEFAULT_INTERVAL_SECS)
+                    .max(1),
+            ),
+            batch_size: lookup("BATCH_SIZE")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_BATCH_SIZE),
+        }
+    }
+}
+
+/// Spawn one background task per enabled provider that periodically fetches jokes
+/// and upserts them, recording each run in the `ingestion_runs` table.
+pub fn spawn(joke_manager: &JokeManager, secrets: &SecretStore) {
+    for provider in joke_manager.providers() {
+        let schedule = ProviderSchedule::from_secrets(secrets, provider.id());
+        if !schedule.enabled || schedule.batch_size == 0 {
+            println!("Ingestion disabled for provider {}", provider.name());
+            continue;
+        }
+
+        let provider_manager = joke_manager.for_provider(provider);
+        let provider_id = provider.id().to_string();
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(schedule.interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                ticker.tick().await;
+                if let Err(e) = run_once(&provider_manager, &provider_id, schedule.batch_size).await {
+                    eprintln!("Failed to record ingestion run for {}: {}", provider_id, e);
+                }
+            }
+        });
+    }
+}
+
+/// Run a single ingestion for one provider. Fetch and save failures are recorded on
+/// the run row; only failures to write the run row itself are returned.
+async fn run_once(joke_manager: &JokeManager, provider: &str, batch_size: usize) -> Result<(), sqlx::Error> {
+    let pool = db::get_pool();
+
+    let (run_id,) = sqlx::query_as::<_, (Uuid,)>(
+        r#"
+        INSERT INTO ingestion_runs (provider, requested_count)
+        VALUES ($1, $2)
+        RETURNING id
+        "#
+    )
+    .bind(provider)
+    .bind(batch_size as i32)
+    .fetch_one(pool)
+    .await?;
+
+    let (fetched_count, saved_count, error) = match joke_manager.get_multiple_jokes(batch_size).await {
+        Ok(fetched) if fetched.jokes.is_empty() => (0, 0, Some(match fetched.failures.first() {
+            Some(failure) => format!(
+                "All {} calls failed, first with {:?} after {}ms: {}",
+                fetched.failures.len(),
+                failure.kind,
+                failure.latency_ms,
+                failure.error
+            ),
+            None => "Provider returned no jokes".to_string(),
+        })),
+        Ok(fetched) => match db::jokes::save_jokes(pool, &fetched.jokes).await {
+            Ok(outcome) => (fetched.jokes.len(), outcome.saved.len(), None),
+            Err(e) => (fetched.jokes.len(), 0, Some(format!("Database error: {}", e))),
+        },
+        Err(e) => (0, 0, Some(format!("Failed to retrieve jokes: {}", e))),
+    };
+
+    if let Some(error) = &error {
+        eprintln!("Ingestion run for {} failed: {}", provider, error);
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE ingestion_runs
+        SET status = $2,
+            fetched_count = $3,
+            saved_count = $4,
+            error = $5,
+            finished_at = CURRENT_TIMESTAMP
+        WHERE id = $1
+        "#
+    )
+    .bind(run_id)
+    .bind(if error.is_some() { "failed" } else { "succeeded" })
+    .bind(fetched_count as i32)
+    .bind(saved_count as i32)
+    .bind(error)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
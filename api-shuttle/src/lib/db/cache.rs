@@ -0,0 +1,28 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use crate::providers::fallback::JokeCache;
+use super::filters::JokeFilters;
+use super::jokes::{self, StoredJoke};
+
+/// Serves stored jokes from the jokes table for the manager's cache fallback
+pub struct DatabaseJokeCache {
+    pool: PgPool,
+}
+
+impl DatabaseJokeCache {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl JokeCache for DatabaseJokeCache {
+    async fn cached_joke(&self, category: Option<&str>) -> Result<Option<StoredJoke>, String> {
+        let filters = JokeFilters {
+            category: category.map(|category| category.to_string()),
+            ..JokeFilters::default()
+        };
+
+        jokes::random(&self.pool, &filters).await.map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,50 @@
+use sqlx::{Postgres, QueryBuilder};
+use crate::providers::categories::{is_canonical, CANONICAL_CATEGORIES};
+
+/// Filters over the indexed columns of the jokes table
+#[derive(Debug, Default)]
+pub struct JokeFilters {
+    /// Canonical category, see `CANONICAL_CATEGORIES`
+    pub category: Option<String>,
+    pub r#type: Option<String>,
+    pub safe: Option<bool>,
+    pub lang: Option<String>,
+    pub provider: Option<String>,
+}
+
+impl JokeFilters {
+    /// Check filter values that the database would otherwise silently never match
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(category) = &self.category {
+            if !is_canonical(category) {
+                return Err(format!("Invalid category, expected one of: {}", CANONICAL_CATEGORIES.join(", ")));
+            }
+        }
+        if let Some(joke_type) = &self.r#type {
+            if joke_type != "single" && joke_type != "twopart" {
+                return Err("Invalid type, expected 'single' or 'twopart'".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Append one equality condition per filter so the column indexes can be used.
+    /// The query must already contain a `WHERE` clause.
+    pub fn push_conditions(&self, builder: &mut QueryBuilder<'_, Postgres>) {
+        if let Some(category) = &self.category {
+            builder.push(" AND category = ").push_bind(category.clone());
+        }
+        if let Some(joke_type) = &self.r#type {
+            builder.push(" AND type = ").push_bind(joke_type.clone());
+        }
+        if let Some(safe) = self.safe {
+            builder.push(" AND safe = ").push_bind(safe);
+        }
+        if let Some(lang) = &self.lang {
+            builder.push(" AND lang = ").push_bind(lang.clone());
+        }
+        if let Some(provider) = &self.provider {
+            builder.push(" AND provider = ").push_bind(provider.clone());
+        }
+    }
+}
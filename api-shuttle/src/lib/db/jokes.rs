@@ -0,0 +1,360 @@
+use std::collections::hash_map::{Entry, HashMap};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::types::Uuid;
+use sqlx::{FromRow, PgPool, Postgres, QueryBuilder};
+use crate::providers::categories::canonical_category;
+use crate::providers::manager::JokeWithProvider;
+use crate::providers::types::{Joke, JokeContent, JokeType};
+use super::filters::JokeFilters;
+
+/// Columns selected for a `StoredJoke`
+const COLUMNS: &str = "id, external_id, joke, category, provider_category, type, safe, lang, provider, created_at, updated_at";
+
+/// A joke as stored in the jokes table
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct StoredJoke {
+    pub id: Uuid,
+    pub external_id: Option<String>,
+    #[sqlx(json)]
+    pub joke: JokeContent,
+    /// Canonical category
+    pub category: Option<String>,
+    /// Category as the provider named it
+    pub provider_category: Option<String>,
+    #[sqlx(rename = "type", try_from = "String")]
+    pub r#type: JokeType,
+    pub safe: bool,
+    pub lang: String,
+    pub provider: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<StoredJoke> for JokeWithProvider {
+    fn from(stored: StoredJoke) -> Self {
+        JokeWithProvider {
+            joke: Joke {
+                id: stored.external_id,
+                joke: stored.joke,
+                category: stored.category,
+                r#type: stored.r#type,
+                safe: Some(stored.safe),
+                lang: Some(stored.lang),
+            },
+            provider: stored.provider,
+        }
+    }
+}
+
+/// A joke ready to be inserted, already checked against the `check_joke_type_content` constraint
+#[derive(Debug, Clone)]
+pub struct NewJoke {
+    pub external_id: Option<String>,
+    pub joke: JokeContent,
+    /// Canonical category
+    pub category: Option<String>,
+    /// Category as the provider named it
+    pub provider_category: Option<String>,
+    pub r#type: JokeType,
+    pub safe: bool,
+    pub lang: String,
+    pub provider: String,
+}
+
+impl TryFrom<&JokeWithProvider> for NewJoke {
+    type Error = String;
+
+    /// Keep only the content fields valid for the joke type, rejecting jokes that
+    /// would fail the check constraint
+    fn try_from(joke_with_provider: &JokeWithProvider) -> Result<Self, Self::Error> {
+        let joke = &joke_with_provider.joke;
+        let content = match joke.r#type {
+            JokeType::Single => match &joke.joke.content {
+                Some(content) => JokeContent {
+                    content: Some(content.clone()),
+                    setup: None,
+                    punchline: None,
+                },
+                None => return Err("Single joke missing content field".to_string()),
+            },
+            JokeType::Twopart => match (&joke.joke.setup, &joke.joke.punchline) {
+                (Some(setup), Some(punchline)) => JokeContent {
+                    content: None,
+                    setup: Some(setup.clone()),
+                    punchline: Some(punchline.clone()),
+                },
+                _ => return Err("Twopart joke missing setup or punchline fields".to_string()),
+            },
+        };
+
+        Ok(NewJoke {
+            external_id: joke.id.clone(),
+            joke: content,
+            category: joke.category.as_deref()
+                .map(|category| canonical_category(&joke_with_provider.provider, category).to_string()),
+            provider_category: joke.category.clone(),
+            r#type: joke.r#type.clone(),
+            safe: joke.safe.unwrap_or(true),
+            lang: joke.lang.as_deref().unwrap_or("en").to_string(),
+            provider: joke_with_provider.provider.clone(),
+        })
+    }
+}
+
+/// Options for `list`, ordered by (created_at, id)
+#[derive(Debug, Clone)]
+pub struct ListOptions {
+    /// Maximum number of rows to return
+    pub limit: i64,
+    /// Only return rows strictly after this (created_at, id) position in the sort order
+    pub after: Option<(DateTime<Utc>, Uuid)>,
+    /// Newest first when true
+    pub descending: bool,
+}
+
+/// A row written by `insert_many`
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct UpsertedJoke {
+    #[sqlx(flatten)]
+    pub joke: StoredJoke,
+    /// True when the row was newly inserted, false when an existing row was refreshed
+    pub inserted: bool,
+}
+
+/// A full-text search match
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SearchHit {
+    #[sqlx(flatten)]
+    pub joke: StoredJoke,
+    /// Relevance score, higher is better
+    pub rank: f32,
+    /// HTML-escaped joke text with matched terms wrapped in `<b>` tags
+    pub snippet: String,
+}
+
+/// A fetched joke that `save_jokes` did not store
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedJoke {
+    pub provider: String,
+    /// Provider's id for the joke
+    pub external_id: Option<String>,
+    pub reason: String,
+}
+
+/// Result of `save_jokes`
+#[derive(Debug, Clone)]
+pub struct SaveOutcome {
+    /// Rows written, one per distinct joke
+    pub saved: Vec<UpsertedJoke>,
+    /// Jokes rejected before reaching the database
+    pub skipped: Vec<SkippedJoke>,
+}
+
+/// Reason given for jokes superseded by a later copy in the same batch
+pub const DUPLICATE_IN_BATCH: &str = "Duplicate in batch";
+
+/// Validate fetched jokes and upsert them in a single transaction, skipping the ones
+/// that would fail the check constraint and those repeated later in the batch
+pub async fn save_jokes(pool: &PgPool, jokes: &[JokeWithProvider]) -> Result<SaveOutcome, sqlx::Error> {
+    let mut new_jokes = Vec::with_capacity(jokes.len());
+    let mut skipped = Vec::new();
+
+    for joke_with_provider in jokes {
+        match NewJoke::try_from(joke_with_provider) {
+            Ok(new_joke) => new_jokes.push(new_joke),
+            Err(reason) => skipped.push(SkippedJoke {
+                provider: joke_with_provider.provider.clone(),
+                external_id: joke_with_provider.joke.id.clone(),
+                reason,
+            }),
+        }
+    }
+
+    let (_, duplicates) = collapse_duplicates(&new_jokes);
+    skipped.extend(duplicates.into_iter().map(|joke| SkippedJoke {
+        provider: joke.provider.clone(),
+        external_id: joke.external_id.clone(),
+        reason: DUPLICATE_IN_BATCH.to_string(),
+    }));
+
+    let saved = insert_many(pool, &new_jokes).await?;
+    Ok(SaveOutcome { saved, skipped })
+}
+
+/// Upsert jokes in a single transaction, returning exactly the stored rows and whether
+/// each one was inserted or refreshed.
+///
+/// Jokes sharing an (external_id, provider) key are collapsed to the last one, since
+/// `ON CONFLICT DO UPDATE` cannot touch the same row twice in one statement. Jokes without
+/// an external_id never conflict, so each one is inserted.
+pub async fn insert_many(pool: &PgPool, jokes: &[NewJoke]) -> Result<Vec<UpsertedJoke>, sqlx::Error> {
+    let (deduplicated, _) = collapse_duplicates(jokes);
+    if deduplicated.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut builder = QueryBuilder::<Postgres>::new(
+        "INSERT INTO jokes (external_id, joke, category, provider_category, type, safe, provider, lang) "
+    );
+    builder.push_values(deduplicated, |mut row, joke| {
+        row.push_bind(joke.external_id.clone())
+            .push_bind(content_json(&joke.joke))
+            .push_bind(joke.category.clone())
+            .push_bind(joke.provider_category.clone())
+            .push_bind(joke.r#type.as_str())
+            .push_bind(joke.safe)
+            .push_bind(joke.provider.clone())
+            .push_bind(joke.lang.clone());
+    });
+    builder.push(format!(
+        r#"
+        ON CONFLICT (external_id, provider)
+        DO UPDATE SET
+            joke = EXCLUDED.joke,
+            category = EXCLUDED.category,
+            provider_category = EXCLUDED.provider_category,
+            type = EXCLUDED.type,
+            safe = EXCLUDED.safe,
+            lang = EXCLUDED.lang,
+            updated_at = CURRENT_TIMESTAMP
+        RETURNING {}, (xmax = 0) AS inserted
+        "#,
+        COLUMNS
+    ));
+
+    let mut tx = pool.begin().await?;
+    // xmax is only set on the row version written by the DO UPDATE branch
+    let stored = builder.build_query_as::<UpsertedJoke>().fetch_all(&mut *tx).await?;
+    tx.commit().await?;
+
+    Ok(stored)
+}
+
+/// Split jokes into the last copy of each (external_id, provider) key, in order of first
+/// appearance, and the earlier copies it supersedes
+fn collapse_duplicates(jokes: &[NewJoke]) -> (Vec<&NewJoke>, Vec<&NewJoke>) {
+    let mut positions: HashMap<(&str, &str), usize> = HashMap::new();
+    let mut deduplicated: Vec<&NewJoke> = Vec::with_capacity(jokes.len());
+    let mut superseded = Vec::new();
+    for joke in jokes {
+        match joke.external_id.as_deref() {
+            Some(external_id) => match positions.entry((external_id, joke.provider.as_str())) {
+                Entry::Occupied(entry) => {
+                    superseded.push(std::mem::replace(&mut deduplicated[*entry.get()], joke));
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(deduplicated.len());
+                    deduplicated.push(joke);
+                }
+            },
+            None => deduplicated.push(joke),
+        }
+    }
+    (deduplicated, superseded)
+}
+
+/// Stored JSON omits absent fields entirely rather than storing nulls
+fn content_json(content: &JokeContent) -> serde_json::Value {
+    let mut json = serde_json::Map::new();
+    for (key, value) in [("content", &content.content), ("setup", &content.setup), ("punchline", &content.punchline)] {
+        if let Some(value) = value {
+            json.insert(key.to_string(), serde_json::Value::String(value.clone()));
+        }
+    }
+    serde_json::Value::Object(json)
+}
+
+/// Fetch a joke by its database id
+pub async fn get(pool: &PgPool, id: Uuid) -> Result<Option<StoredJoke>, sqlx::Error> {
+    sqlx::query_as::<_, StoredJoke>(&format!("SELECT {} FROM jokes WHERE id = $1", COLUMNS))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Number of stored jokes in each category, leaving out jokes without one
+pub async fn category_counts(pool: &PgPool) -> Result<Vec<(String, i64)>, sqlx::Error> {
+    sqlx::query_as("SELECT category, COUNT(*) FROM jokes WHERE category IS NOT NULL GROUP BY category ORDER BY category")
+        .fetch_all(pool)
+        .await
+}
+
+/// Pick a random joke matching `filters`.
+///
+/// Every row carries an indexed `random_key` in [0, 1). We draw a pivot and take the
+/// first row at or above it, wrapping around to the lowest key when nothing is above.
+/// Both lookups are index scans, on `(category, random_key)` when filtering by category,
+/// so the cost does not grow with the table size the way `ORDER BY RANDOM()` does.
+pub async fn random(pool: &PgPool, filters: &JokeFilters) -> Result<Option<StoredJoke>, sqlx::Error> {
+    let pivot: f64 = rand::random();
+
+    for condition in [" AND random_key >= ", " AND random_key < "] {
+        let mut builder = QueryBuilder::<Postgres>::new(format!("SELECT {} FROM jokes WHERE TRUE", COLUMNS));
+        filters.push_conditions(&mut builder);
+        builder.push(condition).push_bind(pivot);
+        builder.push(" ORDER BY random_key LIMIT 1");
+
+        if let Some(joke) = builder.build_query_as::<StoredJoke>().fetch_optional(pool).await? {
+            return Ok(Some(joke));
+        }
+    }
+
+    Ok(None)
+}
+
+/// List jokes matching `filters` using keyset pagination over (created_at, id)
+pub async fn list(pool: &PgPool, filters: &JokeFilters, options: &ListOptions) -> Result<Vec<StoredJoke>, sqlx::Error> {
+    let mut builder = QueryBuilder::<Postgres>::new(format!("SELECT {} FROM jokes WHERE TRUE", COLUMNS));
+    filters.push_conditions(&mut builder);
+
+    // Continue strictly after the given position
+    if let Some((created_at, id)) = options.after {
+        builder
+            .push(if options.descending { " AND (created_at, id) < (" } else { " AND (created_at, id) > (" })
+            .push_bind(created_at)
+            .push(", ")
+            .push_bind(id)
+            .push(")");
+    }
+
+    builder.push(if options.descending {
+        " ORDER BY created_at DESC, id DESC"
+    } else {
+        " ORDER BY created_at ASC, id ASC"
+    });
+    builder.push(" LIMIT ").push_bind(options.limit);
+
+    builder.build_query_as::<StoredJoke>().fetch_all(pool).await
+}
+
+/// Full-text search over joke content, best match first
+pub async fn search(pool: &PgPool, query: &str, filters: &JokeFilters, limit: i64) -> Result<Vec<SearchHit>, sqlx::Error> {
+    // Match against the generated search_vector column so the GIN index can be used.
+    // The text is HTML-escaped before highlighting so only the <b> tags are markup.
+    let mut builder = QueryBuilder::<Postgres>::new(format!(
+        r#"
+        SELECT {},
+            ts_rank(search_vector, query) AS rank,
+            ts_headline('english', replace(replace(replace(replace(
+                concat_ws(' ', joke->>'content', joke->>'setup', joke->>'punchline'),
+                '&', '&amp;'), '<', '&lt;'), '>', '&gt;'), '"', '&quot;'), query) AS snippet
+        FROM jokes, websearch_to_tsquery('english', "#,
+        COLUMNS
+    ));
+    builder.push_bind(query.to_string());
+    builder.push(") AS query WHERE search_vector @@ query");
+    filters.push_conditions(&mut builder);
+    builder.push(" ORDER BY rank DESC, id LIMIT ").push_bind(limit);
+
+    builder.build_query_as::<SearchHit>().fetch_all(pool).await
+}
+
+/// Delete a joke by its database id, returning whether it existed
+pub async fn delete(pool: &PgPool, id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM jokes WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
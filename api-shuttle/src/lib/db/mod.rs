@@ -1,3 +1,7 @@
+pub mod cache;
+pub mod filters;
+pub mod jokes;
+
 use sqlx::{migrate::Migrator, PgPool};
 use tokio::sync::OnceCell;
 use std::path::Path;
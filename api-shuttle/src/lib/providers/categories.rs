@@ -0,0 +1,70 @@
+//! Canonical joke categories shared by all providers
+
+/// Categories jokes are stored and filtered under, whatever the provider calls them
+pub const CANONICAL_CATEGORIES: &[&str] = &[
+    "animal",
+    "career",
+    "celebrity",
+    "christmas",
+    "dad",
+    "dark",
+    "explicit",
+    "fashion",
+    "food",
+    "general",
+    "history",
+    "knock-knock",
+    "money",
+    "movies",
+    "music",
+    "politics",
+    "programming",
+    "pun",
+    "religion",
+    "science",
+    "spooky",
+    "sports",
+    "travel",
+];
+
+/// Category for provider categories with no canonical counterpart
+pub const DEFAULT_CATEGORY: &str = "general";
+
+/// Provider categories whose canonical name differs, as (provider id, provider category, canonical category).
+/// Provider categories already matching a canonical name need no entry.
+pub const CATEGORY_MAPPINGS: &[(&str, &str, &str)] = &[
+    ("jokeapi", "misc", "general"),
+    ("jokeapi", "miscellaneous", "general"),
+    ("sv443", "misc", "general"),
+    ("sv443", "miscellaneous", "general"),
+    ("icanhazdadjoke", "dad jokes", "dad"),
+    ("chucknorris", "dev", "programming"),
+    ("chucknorris", "movie", "movies"),
+    ("chucknorris", "political", "politics"),
+    ("chucknorris", "sport", "sports"),
+    ("chucknorris", "uncategorized", "general"),
+];
+
+/// Whether `category` is one of the canonical categories
+pub fn is_canonical(category: &str) -> bool {
+    CANONICAL_CATEGORIES.contains(&category)
+}
+
+/// The canonical category for a provider's category, e.g. 'programming' for Chuck Norris' 'dev'.
+/// Matching ignores case; unknown categories fall back to `DEFAULT_CATEGORY`.
+pub fn canonical_category(provider_id: &str, category: &str) -> &'static str {
+    mapped_category(provider_id, category).unwrap_or(DEFAULT_CATEGORY)
+}
+
+/// Like `canonical_category`, but `None` for categories outside the taxonomy, such as JokeAPI's 'any'
+pub fn mapped_category(provider_id: &str, category: &str) -> Option<&'static str> {
+    let category = category.to_lowercase();
+    if let Some((_, _, canonical)) = CATEGORY_MAPPINGS
+        .iter()
+        .find(|(provider, provider_category, _)| *provider == provider_id && *provider_category == category)
+    {
+        return Some(canonical);
+    }
+
+    CANONICAL_CATEGORIES.iter().find(|canonical| **canonical == category).copied()
+}
@@ -1,16 +1,36 @@
-use super::types::{Joke, JokeContent, JokeProvider, JokeType};
+use super::config::ProviderConfig;
+use super::error::ProviderError;
+use super::http::HttpClient;
+use super::models::{decode, ChuckNorrisJoke};
+use super::types::{Joke, JokeProvider};
 use async_trait::async_trait;
-use reqwest::Client;
+
+/// Base URL used unless the config sets one
+const DEFAULT_BASE_URL: &str = "https://api.chucknorris.io";
 
 pub struct ChuckNorrisProvider {
-    client: Client,
+    client: HttpClient,
+    base_url: String,
     categories: Vec<String>,
 }
 
 impl ChuckNorrisProvider {
     pub fn new() -> Self {
+        Self::from_config(&ProviderConfig::default(), &HttpClient::default())
+    }
+
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        let config = ProviderConfig {
+            base_url: Some(base_url.into()),
+            ..ProviderConfig::default()
+        };
+        Self::from_config(&config, &HttpClient::default())
+    }
+
+    pub fn from_config(config: &ProviderConfig, client: &HttpClient) -> Self {
         Self {
-            client: Client::new(),
+            client: client.for_provider(config),
+            base_url: config.base_url_or(DEFAULT_BASE_URL),
             categories: vec![
                 "animal".to_string(),
                 "career".to_string(),
@@ -33,73 +53,51 @@ impl ChuckNorrisProvider {
     }
 }
 
+impl Default for ChuckNorrisProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait]
 impl JokeProvider for ChuckNorrisProvider {
+    fn id(&self) -> &str {
+        "chucknorris"
+    }
+
     fn name(&self) -> &str {
         "Chuck Norris Jokes API"
     }
 
     fn base_url(&self) -> &str {
-        "https://api.chucknorris.io"
+        &self.base_url
     }
 
-    async fn get_random_joke(&self) -> Result<Joke, Box<dyn std::error::Error + Send + Sync>> {
+    async fn get_random_joke(&self) -> Result<Joke, ProviderError> {
         let url = format!("{}/jokes/random", self.base_url());
-        let response = self.client.get(&url).send().await?;
-        let data: serde_json::Value = response.json().await?;
-
-        Ok(Joke {
-            id: data["id"].as_str().map(|s| s.to_string()),
-            joke: JokeContent {
-                content: data["value"].as_str().map(|s| s.to_string()),
-                setup: None,
-                punchline: None,
-            },
-            category: data["categories"].as_array()
-                .and_then(|arr| arr.first())
-                .and_then(|cat| cat.as_str())
-                .map(|s| s.to_lowercase())
-                .or_else(|| Some("uncategorized".to_string())),
-            r#type: JokeType::Single,
-            safe: None,
-            lang: None,
-        })
-    }
+        let response = ProviderError::check_status(self.client.get(&url).send().await?)?;
+        let data: ChuckNorrisJoke = decode(response).await?;
 
-    async fn get_joke_by_category(&self, category: &str) -> Result<Joke, Box<dyn std::error::Error + Send + Sync>> {
-        let valid_category = if self.categories.contains(&category.to_lowercase()) {
-            Some(category.to_lowercase())
-        } else {
-            None
-        };
+        Joke::from(data).with_content()
+    }
 
-        if let Some(cat) = valid_category {
-            let url = format!("{}/jokes/random?category={}", self.base_url(), cat);
-            let response = self.client.get(&url).send().await?;
-            let data: serde_json::Value = response.json().await?;
-
-            Ok(Joke {
-                id: data["id"].as_str().map(|s| s.to_string()),
-                joke: JokeContent {
-                    content: data["value"].as_str().map(|s| s.to_string()),
-                    setup: None,
-                    punchline: None,
-                },
-                category: data["categories"].as_array()
-                    .and_then(|arr| arr.first())
-                    .and_then(|cat| cat.as_str())
-                    .map(|s| s.to_lowercase())
-                    .or_else(|| Some(cat)),
-                r#type: JokeType::Single,
-                safe: None,
-                lang: None,
-            })
-        } else {
-            self.get_random_joke().await
+    async fn get_joke_by_category(&self, category: &str) -> Result<Joke, ProviderError> {
+        let cat = category.to_lowercase();
+        if !self.categories.contains(&cat) {
+            return Err(ProviderError::UnsupportedCategory(category.to_string()));
         }
+
+        let url = format!("{}/jokes/random?category={}", self.base_url(), cat);
+        let response = ProviderError::check_status(self.client.get(&url).send().await?)?;
+        let data: ChuckNorrisJoke = decode(response).await?;
+
+        // File jokes without categories under the one asked for
+        let category = data.categories.first().map(|c| c.to_lowercase()).unwrap_or(cat);
+        Joke { category: Some(category), ..Joke::from(data) }.with_content()
     }
 
     fn get_supported_categories(&self) -> Vec<String> {
         self.categories.clone()
     }
-}
\ No newline at end of file
+}
+
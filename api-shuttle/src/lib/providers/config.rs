@@ -0,0 +1,82 @@
+use std::collections::BTreeMap;
+use serde::Deserialize;
+
+/// Ids of the built-in providers, as returned by `JokeProvider::id`
+pub const PROVIDER_IDS: [&str; 6] = [
+    "jokeapi",
+    "icanhazdadjoke",
+    "chucknorris",
+    "officialjoke",
+    "sv443",
+    "jokesone",
+];
+
+/// Settings for all providers, keyed by provider id.
+///
+/// ```toml
+/// [providers.chucknorris]
+/// base_url = "http://localhost:9000"
+/// timeout_secs = 5
+///
+/// [providers.jokesone]
+/// enabled = false
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProvidersConfig {
+    #[serde(default)]
+    pub providers: BTreeMap<String, ProviderConfig>,
+}
+
+/// Settings for one provider. Anything left out uses the provider's default.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProviderConfig {
+    /// Whether the provider is used at all (default: true)
+    pub enabled: Option<bool>,
+    pub base_url: Option<String>,
+    pub api_key: Option<String>,
+    /// Total request timeout, overriding the shared HTTP client's
+    pub timeout_secs: Option<u64>,
+    /// User agent, overriding the shared HTTP client's
+    pub user_agent: Option<String>,
+    /// Weight for the 'weighted' selection strategy
+    pub weight: Option<u32>,
+}
+
+impl ProvidersConfig {
+    pub fn from_toml(contents: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(contents).map_err(|e| format!("Invalid providers config: {}", e))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Reject settings for providers that do not exist
+    pub fn validate(&self) -> Result<(), String> {
+        match self.providers.keys().find(|id| !PROVIDER_IDS.contains(&id.as_str())) {
+            Some(id) => Err(format!(
+                "Unknown provider '{}' in providers config, expected one of: {}",
+                id,
+                PROVIDER_IDS.join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Settings for the provider `id`, defaulted when absent
+    pub fn get(&self, id: &str) -> ProviderConfig {
+        self.providers.get(id).cloned().unwrap_or_default()
+    }
+}
+
+impl ProviderConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.unwrap_or(true)
+    }
+
+    /// Configured base URL without a trailing slash, or `default`
+    pub fn base_url_or(&self, default: &str) -> String {
+        self.base_url.as_deref().unwrap_or(default).trim_end_matches('/').to_string()
+    }
+
+}
@@ -1,54 +1,72 @@
-use super::types::{Joke, JokeContent, JokeProvider, JokeType};
+use super::config::ProviderConfig;
+use super::error::ProviderError;
+use super::http::HttpClient;
+use super::models::{decode, DadJoke};
+use super::types::{Joke, JokeProvider};
 use async_trait::async_trait;
-use reqwest::Client;
+
+/// Base URL used unless the config sets one
+const DEFAULT_BASE_URL: &str = "https://icanhazdadjoke.com";
 
 pub struct DadJokesProvider {
-    client: Client,
+    client: HttpClient,
+    base_url: String,
 }
 
 impl DadJokesProvider {
     pub fn new() -> Self {
+        Self::from_config(&ProviderConfig::default(), &HttpClient::default())
+    }
+
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        let config = ProviderConfig {
+            base_url: Some(base_url.into()),
+            ..ProviderConfig::default()
+        };
+        Self::from_config(&config, &HttpClient::default())
+    }
+
+    pub fn from_config(config: &ProviderConfig, client: &HttpClient) -> Self {
         Self {
-            client: Client::new(),
+            client: client.for_provider(config),
+            base_url: config.base_url_or(DEFAULT_BASE_URL),
         }
     }
 }
 
+impl Default for DadJokesProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait]
 impl JokeProvider for DadJokesProvider {
+    fn id(&self) -> &str {
+        "icanhazdadjoke"
+    }
+
     fn name(&self) -> &str {
         "icanhazdadjoke"
     }
 
     fn base_url(&self) -> &str {
-        "https://icanhazdadjoke.com"
+        &self.base_url
     }
 
-    async fn get_random_joke(&self) -> Result<Joke, Box<dyn std::error::Error + Send + Sync>> {
+    async fn get_random_joke(&self) -> Result<Joke, ProviderError> {
         let response = self.client
             .get(self.base_url())
             .header("Accept", "application/json")
-            .header("User-Agent", "Jokes App (https://github.com/yourapp)")
             .send()
             .await?;
+        let data: DadJoke = decode(ProviderError::check_status(response)?).await?;
 
-        let data: serde_json::Value = response.json().await?;
-
-        Ok(Joke {
-            id: data["id"].as_str().map(|s| s.to_string()),
-            joke: JokeContent {
-                content: data["joke"].as_str().map(|s| s.to_string()),
-                setup: None,
-                punchline: None,
-            },
-            category: Some("dad jokes".to_string()),
-            r#type: JokeType::Single,
-            safe: None,
-            lang: None,
-        })
+        Joke::from(data).with_content()
     }
 
     fn get_supported_categories(&self) -> Vec<String> {
         vec!["dad jokes".to_string()]
     }
-}
\ No newline at end of file
+}
+
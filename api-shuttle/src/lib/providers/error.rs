@@ -0,0 +1,149 @@
+use std::fmt;
+use std::time::Duration;
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Response, StatusCode};
+
+/// Error returned by a `JokeProvider` or the `JokeManager`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProviderError {
+    /// Connection, DNS, TLS or other transport failure
+    Network(String),
+    /// The provider did not answer in time
+    Timeout,
+    /// The provider answered 429 Too Many Requests
+    RateLimited {
+        /// Delay requested by the provider's Retry-After header, if any
+        retry_after: Option<Duration>,
+    },
+    /// The provider answered with another non-success status
+    HttpStatus {
+        status: u16,
+        /// Delay requested by a Retry-After header, typically sent with 503
+        retry_after: Option<Duration>,
+    },
+    /// The response body could not be decoded
+    Decode(String),
+    /// The provider answered with an error payload instead of a joke
+    Upstream(String),
+    /// The provider has no joke matching the request, e.g. none left in a category
+    NoMatchingJoke(String),
+    /// The provider does not serve this category
+    UnsupportedCategory(String),
+    /// The provider answered but the joke had no text
+    EmptyJoke,
+    /// The manager has no providers to ask
+    NoProviders,
+    /// Every provider that could serve the call has an open circuit
+    NoHealthyProviders,
+    /// No managed provider matches the requested name
+    UnknownProvider(String),
+}
+
+impl ProviderError {
+    /// Map a non-success response to an error, passing successful responses through
+    pub fn check_status(response: Response) -> Result<Response, ProviderError> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(ProviderError::RateLimited { retry_after });
+        }
+
+        Err(ProviderError::HttpStatus { status: status.as_u16(), retry_after })
+    }
+
+    /// HTTP status returned by the provider, when the error came from one
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ProviderError::RateLimited { .. } => Some(StatusCode::TOO_MANY_REQUESTS.as_u16()),
+            ProviderError::HttpStatus { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// Whether the same call may succeed if tried again.
+    ///
+    /// Transport failures, timeouts, 429 and 5xx are transient; other 4xx, undecodable
+    /// bodies, error payloads and unsupported categories will fail the same way every time.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ProviderError::Network(_) | ProviderError::Timeout | ProviderError::RateLimited { .. } => true,
+            ProviderError::HttpStatus { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
+
+    /// Delay the provider asked for before the next attempt
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            ProviderError::RateLimited { retry_after } | ProviderError::HttpStatus { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+/// Retry-After is either a number of seconds or an HTTP date
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(seconds) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value.trim()).ok()?;
+    let delay = date.signed_duration_since(chrono::Utc::now());
+    Some(delay.to_std().unwrap_or(Duration::ZERO))
+}
+
+impl fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProviderError::Network(e) => write!(f, "Network error: {}", e),
+            ProviderError::Timeout => write!(f, "Provider timed out"),
+            ProviderError::RateLimited { retry_after: Some(delay) } => {
+                write!(f, "Rate limited by provider, retry after {}s", delay.as_secs())
+            }
+            ProviderError::RateLimited { retry_after: None } => write!(f, "Rate limited by provider"),
+            ProviderError::HttpStatus { status, .. } => write!(f, "Provider returned HTTP {}", status),
+            ProviderError::Decode(e) => write!(f, "Failed to decode provider response: {}", e),
+            ProviderError::Upstream(message) => write!(f, "Provider returned an error: {}", message),
+            ProviderError::NoMatchingJoke(message) => write!(f, "Provider has no matching joke: {}", message),
+            ProviderError::UnsupportedCategory(category) => write!(f, "Category '{}' is not supported", category),
+            ProviderError::EmptyJoke => write!(f, "Provider returned a joke without content"),
+            ProviderError::NoProviders => write!(f, "No providers available"),
+            ProviderError::NoHealthyProviders => write!(f, "No healthy providers available"),
+            ProviderError::UnknownProvider(name) => write!(f, "Provider '{}' not found", name),
+        }
+    }
+}
+
+impl std::error::Error for ProviderError {}
+
+impl From<reqwest::Error> for ProviderError {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            ProviderError::Timeout
+        } else if let Some(status) = error.status() {
+            if status == StatusCode::TOO_MANY_REQUESTS {
+                ProviderError::RateLimited { retry_after: None }
+            } else {
+                ProviderError::HttpStatus { status: status.as_u16(), retry_after: None }
+            }
+        } else if error.is_decode() {
+            ProviderError::Decode(error.to_string())
+        } else {
+            ProviderError::Network(error.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for ProviderError {
+    fn from(error: serde_json::Error) -> Self {
+        ProviderError::Decode(error.to_string())
+    }
+}
@@ -0,0 +1,110 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use async_trait::async_trait;
+use serde::Serialize;
+use crate::db::jokes::StoredJoke;
+use super::error::ProviderError;
+use super::manager::JokeWithProvider;
+
+/// What `JokeManager` tries, in order, when the chosen provider fails to serve a joke
+#[derive(Debug, Clone, Default)]
+pub struct FallbackPolicy {
+    /// Empty means fail with the provider's error
+    pub steps: Vec<FallbackStep>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FallbackStep {
+    /// Try each other healthy provider able to serve the call once
+    OtherProvider,
+    /// Serve a previously stored joke from the `JokeCache`
+    Cache,
+    /// Serve a random joke from any category when a category call cannot be served
+    AnyCategory,
+}
+
+impl FallbackPolicy {
+    /// Fail with the provider's error
+    pub fn fail() -> Self {
+        Self::default()
+    }
+
+    /// Parse a comma-separated list of steps: 'other_provider', 'cache' and 'any_category', or 'fail'
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let mut steps = Vec::new();
+        for step in value.split(',').map(|step| step.trim().to_lowercase().replace('-', "_")) {
+            match step.as_str() {
+                "" | "fail" => {}
+                "other_provider" => steps.push(FallbackStep::OtherProvider),
+                "cache" => steps.push(FallbackStep::Cache),
+                "any_category" => steps.push(FallbackStep::AnyCategory),
+                other => return Err(format!("Unknown fallback step: {}", other)),
+            }
+        }
+        Ok(Self { steps })
+    }
+}
+
+/// Source of previously stored jokes for `FallbackStep::Cache`
+#[async_trait]
+pub trait JokeCache: Send + Sync {
+    /// A stored joke, in `category` when given
+    async fn cached_joke(&self, category: Option<&str>) -> Result<Option<StoredJoke>, String>;
+}
+
+/// How a fallback produced a joke
+#[derive(Debug, Clone)]
+pub struct FallbackUsed {
+    pub step: FallbackStep,
+    /// Error from the provider that was chosen first
+    pub error: ProviderError,
+}
+
+/// A joke served by `JokeManager`, noting whether the fallback policy produced it
+#[derive(Debug, Clone)]
+pub struct ServedJoke {
+    pub joke: JokeWithProvider,
+    /// Set when the joke did not come from the first provider chosen
+    pub fallback: Option<FallbackUsed>,
+    /// The stored row a `FallbackStep::Cache` joke came from; such jokes should not be
+    /// saved again
+    pub stored: Option<StoredJoke>,
+}
+
+/// Number of calls served by each fallback step, and of calls that failed anyway
+#[derive(Debug, Default)]
+pub struct FallbackStats {
+    other_provider: AtomicU64,
+    cache: AtomicU64,
+    any_category: AtomicU64,
+    failed: AtomicU64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FallbackCounts {
+    pub other_provider: u64,
+    pub cache: u64,
+    pub any_category: u64,
+    pub failed: u64,
+}
+
+impl FallbackStats {
+    pub fn record(&self, step: Option<FallbackStep>) {
+        let counter = match step {
+            Some(FallbackStep::OtherProvider) => &self.other_provider,
+            Some(FallbackStep::Cache) => &self.cache,
+            Some(FallbackStep::AnyCategory) => &self.any_category,
+            None => &self.failed,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn counts(&self) -> FallbackCounts {
+        FallbackCounts {
+            other_provider: self.other_provider.load(Ordering::Relaxed),
+            cache: self.cache.load(Ordering::Relaxed),
+            any_category: self.any_category.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+        }
+    }
+}
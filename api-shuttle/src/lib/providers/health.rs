@@ -0,0 +1,280 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use super::error::ProviderError;
+
+/// When a provider's circuit opens and how long it stays open
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    /// Number of most recent calls used for the error rate and latency stats
+    pub window_size: usize,
+    /// Calls needed in the window before the error rate can open the circuit
+    pub min_calls: usize,
+    /// Error rate in [0, 1] at which the circuit opens
+    pub failure_rate_threshold: f64,
+    /// How long an open circuit rejects calls before letting a probe through
+    pub open_duration: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            window_size: 20,
+            min_calls: 5,
+            failure_rate_threshold: 0.5,
+            open_duration: Duration::from_secs(30),
+        }
+    }
+}
+
+/// State of a provider's circuit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Calls go through normally
+    Closed,
+    /// Calls are skipped until the open duration has passed
+    Open,
+    /// A single probe call decides whether to close or reopen the circuit
+    HalfOpen,
+}
+
+/// Point-in-time health of one provider
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderHealth {
+    /// Provider id, as stored in the jokes.provider column
+    pub provider: String,
+    pub provider_name: String,
+    pub state: CircuitState,
+    /// Share of failed calls in the rolling window
+    pub error_rate: f64,
+    /// Calls in the rolling window
+    pub window_calls: usize,
+    pub consecutive_failures: u32,
+    pub total_calls: u64,
+    pub total_failures: u64,
+    /// Mean latency over the rolling window
+    pub avg_latency_ms: Option<u64>,
+    /// 95th percentile latency over the rolling window
+    pub p95_latency_ms: Option<u64>,
+    pub last_error: Option<String>,
+    /// When an open circuit will let the next probe through
+    pub retry_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Call {
+    success: bool,
+    latency_ms: u64,
+}
+
+#[derive(Debug)]
+struct BreakerState {
+    state: CircuitState,
+    opened_at: Option<Instant>,
+    probe_in_flight: bool,
+    window: VecDeque<Call>,
+    consecutive_failures: u32,
+    total_calls: u64,
+    total_failures: u64,
+    last_error: Option<String>,
+}
+
+/// A call let through by `CircuitBreaker::acquire`. Dropping it without recording the
+/// outcome, e.g. when the caller's future is cancelled, frees a half-open circuit's probe.
+#[must_use]
+#[derive(Debug)]
+pub struct CallPermit<'a> {
+    breaker: &'a CircuitBreaker,
+    probe: bool,
+}
+
+impl CallPermit<'_> {
+    /// Record the outcome of the call
+    pub fn record(mut self, result: Result<(), &ProviderError>, latency: Duration) {
+        self.probe = false;
+        self.breaker.record(result, latency);
+    }
+}
+
+impl Drop for CallPermit<'_> {
+    fn drop(&mut self) {
+        if self.probe {
+            self.breaker.release_probe();
+        }
+    }
+}
+
+/// Circuit breaker and rolling stats for a single provider
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            state: Mutex::new(BreakerState {
+                state: CircuitState::Closed,
+                opened_at: None,
+                probe_in_flight: false,
+                window: VecDeque::with_capacity(config.window_size),
+                consecutive_failures: 0,
+                total_calls: 0,
+                total_failures: 0,
+                last_error: None,
+            }),
+            config,
+        }
+    }
+
+    /// Whether `acquire` would currently let a call through
+    pub fn is_available(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        match state.state {
+            CircuitState::Closed => true,
+            CircuitState::Open => self.cooled_down(&state),
+            CircuitState::HalfOpen => !state.probe_in_flight,
+        }
+    }
+
+    /// Reserve a call. An open circuit that has cooled down moves to half-open and
+    /// hands out its single probe; further calls are refused until the probe is recorded
+    /// or its permit is dropped.
+    pub fn acquire(&self) -> Option<CallPermit<'_>> {
+        let mut state = self.state.lock().unwrap();
+        let probe = match state.state {
+            CircuitState::Closed => false,
+            CircuitState::Open if self.cooled_down(&state) => {
+                state.state = CircuitState::HalfOpen;
+                true
+            }
+            CircuitState::Open => return None,
+            CircuitState::HalfOpen if !state.probe_in_flight => true,
+            CircuitState::HalfOpen => return None,
+        };
+        state.probe_in_flight |= probe;
+
+        Some(CallPermit { breaker: self, probe })
+    }
+
+    /// Hand back a probe whose outcome will never be recorded
+    fn release_probe(&self) {
+        let mut state = self.state.lock().unwrap();
+        if state.state == CircuitState::HalfOpen {
+            state.probe_in_flight = false;
+        }
+    }
+
+    /// Record the outcome of a call
+    pub fn record(&self, result: Result<(), &ProviderError>, latency: Duration) {
+        let mut state = self.state.lock().unwrap();
+
+        // A category the provider does not serve, or has no joke left in, says nothing
+        // about its health
+        if let Err(ProviderError::UnsupportedCategory(_) | ProviderError::NoMatchingJoke(_)) = result {
+            state.probe_in_flight = false;
+            return;
+        }
+
+        let success = result.is_ok();
+        if state.window.len() >= self.config.window_size.max(1) {
+            state.window.pop_front();
+        }
+        state.window.push_back(Call {
+            success,
+            latency_ms: latency.as_millis() as u64,
+        });
+        state.total_calls += 1;
+
+        if let Err(error) = result {
+            state.total_failures += 1;
+            state.consecutive_failures += 1;
+            state.last_error = Some(error.to_string());
+        } else {
+            state.consecutive_failures = 0;
+        }
+
+        match state.state {
+            CircuitState::HalfOpen if success => {
+                state.state = CircuitState::Closed;
+                state.opened_at = None;
+                state.probe_in_flight = false;
+                // Start over so the failures that opened the circuit do not reopen it
+                state.window.clear();
+            }
+            CircuitState::HalfOpen => self.open(&mut state),
+            CircuitState::Closed
+                if !success
+                    && state.window.len() >= self.config.min_calls
+                    && error_rate(&state.window) >= self.config.failure_rate_threshold =>
+            {
+                self.open(&mut state)
+            }
+            _ => {}
+        }
+    }
+
+    /// Mean latency over the rolling window
+    pub fn avg_latency_ms(&self) -> Option<u64> {
+        let state = self.state.lock().unwrap();
+        (!state.window.is_empty())
+            .then(|| state.window.iter().map(|call| call.latency_ms).sum::<u64>() / state.window.len() as u64)
+    }
+
+    /// Current state and stats
+    pub fn snapshot(&self, provider: &str, provider_name: &str) -> ProviderHealth {
+        let state = self.state.lock().unwrap();
+
+        let mut latencies: Vec<u64> = state.window.iter().map(|call| call.latency_ms).collect();
+        latencies.sort_unstable();
+        let avg_latency_ms = (!latencies.is_empty())
+            .then(|| latencies.iter().sum::<u64>() / latencies.len() as u64);
+        let p95_latency_ms = (!latencies.is_empty())
+            .then(|| latencies[(latencies.len() * 95).div_ceil(100) - 1]);
+
+        let retry_at = match (state.state, state.opened_at) {
+            (CircuitState::Open, Some(opened_at)) => {
+                let remaining = self.config.open_duration.saturating_sub(opened_at.elapsed());
+                chrono::Duration::from_std(remaining).ok().map(|remaining| Utc::now() + remaining)
+            }
+            _ => None,
+        };
+
+        ProviderHealth {
+            provider: provider.to_string(),
+            provider_name: provider_name.to_string(),
+            state: state.state,
+            error_rate: error_rate(&state.window),
+            window_calls: state.window.len(),
+            consecutive_failures: state.consecutive_failures,
+            total_calls: state.total_calls,
+            total_failures: state.total_failures,
+            avg_latency_ms,
+            p95_latency_ms,
+            last_error: state.last_error.clone(),
+            retry_at,
+        }
+    }
+
+    fn cooled_down(&self, state: &BreakerState) -> bool {
+        state.opened_at.is_some_and(|opened_at| opened_at.elapsed() >= self.config.open_duration)
+    }
+
+    fn open(&self, state: &mut BreakerState) {
+        state.state = CircuitState::Open;
+        state.opened_at = Some(Instant::now());
+        state.probe_in_flight = false;
+    }
+}
+
+fn error_rate(window: &VecDeque<Call>) -> f64 {
+    if window.is_empty() {
+        return 0.0;
+    }
+    let failures = window.iter().filter(|call| !call.success).count();
+    failures as f64 / window.len() as f64
+}
@@ -0,0 +1,88 @@
+use std::time::Duration;
+use reqwest::{Client, IntoUrl, Proxy, RequestBuilder};
+use super::config::ProviderConfig;
+
+/// Identifies the service to upstream APIs unless configured otherwise
+pub const DEFAULT_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// Settings for the HTTP client shared by all providers
+#[derive(Debug, Clone)]
+pub struct HttpClientConfig {
+    /// Time allowed to establish a connection
+    pub connect_timeout: Duration,
+    /// Time allowed between reads of the response
+    pub read_timeout: Duration,
+    /// Time allowed for a whole request, unless a provider overrides it
+    pub timeout: Duration,
+    pub user_agent: String,
+    /// Proxy for all requests, e.g. 'http://proxy.internal:3128'
+    pub proxy: Option<String>,
+    /// Ask for and decompress gzip responses
+    pub gzip: bool,
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(5),
+            read_timeout: Duration::from_secs(10),
+            timeout: Duration::from_secs(15),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            proxy: None,
+            gzip: true,
+        }
+    }
+}
+
+impl HttpClientConfig {
+    pub fn build(&self) -> Result<HttpClient, String> {
+        let mut builder = Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .read_timeout(self.read_timeout)
+            .timeout(self.timeout)
+            .user_agent(&self.user_agent)
+            .gzip(self.gzip);
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(Proxy::all(proxy).map_err(|e| format!("Invalid proxy {}: {}", proxy, e))?);
+        }
+
+        let client = builder.build().map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+        Ok(HttpClient { client, timeout: None, user_agent: None })
+    }
+}
+
+/// HTTP client used by providers. Clones share one connection pool.
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    client: Client,
+    timeout: Option<Duration>,
+    user_agent: Option<String>,
+}
+
+impl HttpClient {
+    /// The same client, applying a provider's timeout and user agent to its requests
+    pub fn for_provider(&self, config: &ProviderConfig) -> Self {
+        Self {
+            client: self.client.clone(),
+            timeout: config.timeout_secs.map(Duration::from_secs).or(self.timeout),
+            user_agent: config.user_agent.clone().or_else(|| self.user_agent.clone()),
+        }
+    }
+
+    pub fn get(&self, url: impl IntoUrl) -> RequestBuilder {
+        let mut request = self.client.get(url);
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            request = request.header(reqwest::header::USER_AGENT, user_agent);
+        }
+        request
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        HttpClientConfig::default().build().expect("Default HTTP client config is valid")
+    }
+}
@@ -1,114 +1,90 @@
-use super::types::{Joke, JokeContent, JokeProvider, JokeType};
+use super::config::ProviderConfig;
+use super::error::ProviderError;
+use super::http::HttpClient;
+use super::models::{decode, JokesOneResponse};
+use super::types::{Joke, JokeProvider};
 use async_trait::async_trait;
-use reqwest::Client;
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
 
+/// Base URL used unless the config sets one
+const DEFAULT_BASE_URL: &str = "https://api.jokes.one";
+
+/// Serves only the joke of the day, so it lists no categories and category calls are
+/// never routed to it
 pub struct JokesOneProvider {
-    client: Client,
-    api_key: Option<String>,
+    client: HttpClient,
+    base_url: String,
+    /// Sent as `X-JokesOne-Api-Secret`, checked to be a valid header value up front
+    api_key: Option<HeaderValue>,
 }
 
 impl JokesOneProvider {
-    pub fn new(api_key: Option<String>) -> Self {
-        Self {
-            client: Client::new(),
+    pub fn new(api_key: Option<String>) -> Result<Self, String> {
+        let config = ProviderConfig {
             api_key,
-        }
+            ..ProviderConfig::default()
+        };
+        Self::from_config(&config, &HttpClient::default())
+    }
+
+    pub fn with_base_url(base_url: impl Into<String>, api_key: Option<String>) -> Result<Self, String> {
+        let config = ProviderConfig {
+            base_url: Some(base_url.into()),
+            api_key,
+            ..ProviderConfig::default()
+        };
+        Self::from_config(&config, &HttpClient::default())
+    }
+
+    /// Fails when the API key cannot be sent as a header, e.g. because of a stray newline
+    pub fn from_config(config: &ProviderConfig, client: &HttpClient) -> Result<Self, String> {
+        let api_key = config.api_key.as_deref()
+            .map(|key| {
+                let mut value = HeaderValue::from_str(key)
+                    .map_err(|_| "Invalid Jokes One API key: it contains characters not allowed in a header".to_string())?;
+                value.set_sensitive(true);
+                Ok::<_, String>(value)
+            })
+            .transpose()?;
+
+        Ok(Self {
+            client: client.for_provider(config),
+            base_url: config.base_url_or(DEFAULT_BASE_URL),
+            api_key,
+        })
     }
 }
 
 #[async_trait]
 impl JokeProvider for JokesOneProvider {
+    fn id(&self) -> &str {
+        "jokesone"
+    }
+
     fn name(&self) -> &str {
         "Jokes One API"
     }
 
     fn base_url(&self) -> &str {
-        "https://api.jokes.one"
+        &self.base_url
     }
 
-    async fn get_random_joke(&self) -> Result<Joke, Box<dyn std::error::Error + Send + Sync>> {
-        let mut headers = reqwest::header::HeaderMap::new();
-        headers.insert("Content-Type", "application/json".parse().unwrap());
+    async fn get_random_joke(&self) -> Result<Joke, ProviderError> {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
 
-        if let Some(ref key) = self.api_key {
-            headers.insert("X-JokesOne-Api-Secret", key.parse().unwrap());
+        if let Some(key) = &self.api_key {
+            headers.insert("X-JokesOne-Api-Secret", key.clone());
         }
 
         let response = self.client
-            .get(&format!("{}/jod", self.base_url()))
+            .get(format!("{}/jod", self.base_url()))
             .headers(headers)
             .send()
-            .await;
+            .await?;
+        let data: JokesOneResponse = decode(ProviderError::check_status(response)?).await?;
 
-        match response {
-            Ok(resp) => {
-                if resp.status().is_success() {
-                    let data: serde_json::Value = resp.json().await?;
-                    Ok(Joke {
-                        id: data["joke"].as_array()
-                            .and_then(|arr| arr.first())
-                            .and_then(|j| j["id"].as_str())
-                            .map(|s| s.to_string()),
-                        joke: JokeContent {
-                            content: data["joke"].as_array()
-                                .and_then(|arr| arr.first())
-                                .and_then(|j| j["text"].as_str())
-                                .map(|s| s.to_string())
-                                .or_else(|| data["contents"]["jokes"].as_array()
-                                    .and_then(|arr| arr.first())
-                                    .and_then(|j| j["joke"]["text"].as_str())
-                                    .map(|s| s.to_string())),
-                            setup: None,
-                            punchline: None,
-                        },
-                        category: data["joke"].as_array()
-                            .and_then(|arr| arr.first())
-                            .and_then(|j| j["category"].as_str())
-                            .map(|s| s.to_string()),
-                        r#type: JokeType::Single,
-                        safe: None,
-                        lang: None,
-                    })
-                } else {
-                    // Fallback joke if API fails
-                    Ok(Joke {
-                        joke: JokeContent {
-                            content: Some("Why don't scientists trust atoms? Because they make up everything!".to_string()),
-                            setup: None,
-                            punchline: None,
-                        },
-                        category: Some("science".to_string()),
-                        r#type: JokeType::Single,
-                        id: None,
-                        safe: None,
-                        lang: None,
-                    })
-                }
-            }
-            Err(_) => {
-                // Fallback joke if network fails
-                Ok(Joke {
-                    joke: JokeContent {
-                        content: Some("Why don't scientists trust atoms? Because they make up everything!".to_string()),
-                        setup: None,
-                        punchline: None,
-                    },
-                    category: Some("science".to_string()),
-                    r#type: JokeType::Single,
-                    id: None,
-                    safe: None,
-                    lang: None,
-                })
-            }
-        }
+        Joke::try_from(data)?.with_content()
     }
+}
 
-    fn get_supported_categories(&self) -> Vec<String> {
-        vec![
-            "general".to_string(),
-            "dad".to_string(),
-            "programming".to_string(),
-            "science".to_string(),
-        ]
-    }
-}
\ No newline at end of file
@@ -1,120 +1,409 @@
+use super::categories::{mapped_category, CANONICAL_CATEGORIES};
+use super::config::ProvidersConfig;
+use super::error::ProviderError;
+use super::fallback::{FallbackCounts, FallbackPolicy, FallbackStats, FallbackStep, FallbackUsed, JokeCache, ServedJoke};
+use super::health::{CallPermit, CircuitBreaker, CircuitBreakerConfig, ProviderHealth};
+use super::http::HttpClient;
+use super::registry::ProviderRegistry;
+use super::retry::{RetryPolicy, RetryingProvider};
+use super::selection::{ProviderSelector, SelectionStrategy};
 use super::types::{Joke, JokeProvider};
-use rand::Rng;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Instant;
 
 #[derive(Clone)]
 pub struct JokeManager {
     providers: Vec<Arc<dyn JokeProvider>>,
+    /// Providers turned off in the config, listed by `get_providers` but never called
+    disabled: Vec<Arc<dyn JokeProvider>>,
+    /// Circuit breakers keyed by provider id, shared between clones
+    breakers: Arc<HashMap<String, CircuitBreaker>>,
+    selector: Arc<ProviderSelector>,
+    fallback: FallbackPolicy,
+    cache: Option<Arc<dyn JokeCache>>,
+    fallback_stats: Arc<FallbackStats>,
 }
 
 impl JokeManager {
     pub fn new(providers: Vec<Arc<dyn JokeProvider>>) -> Self {
-        Self { providers }
+        let breakers = breakers_for(&providers, &CircuitBreakerConfig::default());
+        Self {
+            providers,
+            disabled: Vec::new(),
+            breakers,
+            selector: Arc::new(ProviderSelector::default()),
+            fallback: FallbackPolicy::fail(),
+            cache: None,
+            fallback_stats: Arc::new(FallbackStats::default()),
+        }
     }
 
+    /// Every built-in provider with its default settings
     pub fn with_all_providers() -> Self {
-        Self::new(super::ALL_PROVIDERS.clone())
+        Self::from_registry(&ProviderRegistry::from_config(&ProvidersConfig::default(), &HttpClient::default())
+            .expect("Default providers config is valid"))
+    }
+
+    /// The providers enabled in `registry`, also listing the disabled ones
+    pub fn from_registry(registry: &ProviderRegistry) -> Self {
+        Self {
+            disabled: registry.disabled().to_vec(),
+            ..Self::new(registry.providers().to_vec())
+        }
+    }
+
+    /// Retry every provider call according to `policy`
+    pub fn with_retry_policy(self, policy: RetryPolicy) -> Self {
+        let providers = self.providers.into_iter()
+            .map(|provider| Arc::new(RetryingProvider::new(provider, policy.clone())) as Arc<dyn JokeProvider>)
+            .collect();
+        Self { providers, ..self }
+    }
+
+    /// Replace the circuit breakers, resetting their state
+    pub fn with_circuit_breaker(self, config: CircuitBreakerConfig) -> Self {
+        let breakers = breakers_for(&self.providers, &config);
+        Self { breakers, ..self }
+    }
+
+    /// Choose providers with `strategy` instead of uniformly at random
+    pub fn with_selection_strategy(self, strategy: SelectionStrategy) -> Self {
+        Self {
+            selector: Arc::new(ProviderSelector::new(strategy)),
+            ..self
+        }
+    }
+
+    /// What to do when the chosen provider fails to serve a single joke
+    pub fn with_fallback_policy(self, fallback: FallbackPolicy) -> Self {
+        Self { fallback, ..self }
+    }
+
+    /// Where `FallbackStep::Cache` reads stored jokes from
+    pub fn with_joke_cache(self, cache: Arc<dyn JokeCache>) -> Self {
+        Self {
+            cache: Some(cache),
+            ..self
+        }
+    }
+
+    /// A manager for a single provider that shares this manager's circuit breakers
+    pub fn for_provider(&self, provider: &Arc<dyn JokeProvider>) -> Self {
+        Self {
+            providers: vec![provider.clone()],
+            ..self.clone()
+        }
+    }
+
+    /// Providers managed by this manager
+    pub fn providers(&self) -> &[Arc<dyn JokeProvider>] {
+        &self.providers
+    }
+
+    /// The provider with this `id`
+    pub fn provider(&self, id: &str) -> Option<&Arc<dyn JokeProvider>> {
+        self.providers.iter().find(|provider| provider.id() == id)
+    }
+
+    /// Circuit state and rolling stats for every provider
+    pub fn get_health(&self) -> Vec<ProviderHealth> {
+        self.providers.iter()
+            .filter_map(|provider| self.breakers.get(provider.id())
+                .map(|breaker| breaker.snapshot(provider.id(), provider.name())))
+            .collect()
+    }
+
+    fn breaker(&self, provider: &Arc<dyn JokeProvider>) -> Option<&CircuitBreaker> {
+        self.breakers.get(provider.id())
     }
 
-    /// Get a random joke from a random provider
-    pub async fn get_random_joke(&self) -> Result<JokeWithProvider, Box<dyn std::error::Error + Send + Sync>> {
+    /// Pick a candidate with the selection strategy among those whose circuit lets a call
+    /// through, along with the permit for that call
+    fn pick<'a, T>(
+        &self,
+        mut candidates: Vec<T>,
+        provider: impl Fn(&T) -> &'a Arc<dyn JokeProvider>,
+    ) -> Option<(T, Option<CallPermit<'_>>)> {
+        candidates.retain(|candidate| self.breaker(provider(candidate)).is_none_or(|b| b.is_available()));
+
+        while !candidates.is_empty() {
+            let providers: Vec<_> = candidates.iter().map(&provider).collect();
+            let index = self.selector.choose(&providers, |provider| {
+                self.breaker(provider).and_then(|breaker| breaker.avg_latency_ms())
+            });
+            let candidate = candidates.remove(index);
+            // Another call may have taken a half-open circuit's probe in the meantime
+            match self.breaker(provider(&candidate)).map(CircuitBreaker::acquire) {
+                None => return Some((candidate, None)),
+                Some(Some(permit)) => return Some((candidate, Some(permit))),
+                Some(None) => {}
+            }
+        }
+        None
+    }
+
+    /// Pick a healthy provider
+    fn random_provider(&self) -> Result<(&Arc<dyn JokeProvider>, Option<CallPermit<'_>>), ProviderError> {
         if self.providers.is_empty() {
-            return Err("No providers available".into());
+            return Err(ProviderError::NoProviders);
         }
+        self.pick(self.providers.iter().collect(), |provider| *provider)
+            .ok_or(ProviderError::NoHealthyProviders)
+    }
 
-        let mut rng = rand::thread_rng();
-        let index = rng.gen_range(0..self.providers.len());
-        let provider = &self.providers[index];
+    /// Call a provider, recording the outcome on its circuit breaker. Calls made without
+    /// a permit bypass the circuit but are still recorded.
+    async fn call(
+        &self,
+        provider: &Arc<dyn JokeProvider>,
+        category: Option<&str>,
+        permit: Option<CallPermit<'_>>,
+    ) -> (Result<Joke, ProviderError>, u64) {
+        let started = Instant::now();
+        let result = match category {
+            Some(category) => provider.get_joke_by_category(category).await,
+            None => provider.get_random_joke().await,
+        }.and_then(Joke::with_content);
+        let latency = started.elapsed();
 
-        let joke = provider.get_random_joke().await?;
-        Ok(JokeWithProvider {
-            joke,
-            provider: provider.base_url().to_string(),
-        })
+        let outcome = result.as_ref().map(|_| ());
+        match (permit, self.breaker(provider)) {
+            (Some(permit), _) => permit.record(outcome, latency),
+            (None, Some(breaker)) => breaker.record(outcome, latency),
+            (None, None) => {}
+        }
+        (result, latency.as_millis() as u64)
     }
 
-    /// Get a joke from a specific provider
-    pub async fn get_joke_from_provider(&self, provider_name: &str) -> Result<JokeWithProvider, Box<dyn std::error::Error + Send + Sync>> {
-        let provider = self.providers.iter()
-            .find(|p| p.name().to_lowercase().contains(&provider_name.to_lowercase()))
-            .ok_or_else(|| format!("Provider '{}' not found", provider_name))?;
+    /// Call a candidate chosen by the selection strategy, applying the fallback policy
+    /// when that fails. `category` is the canonical category asked for, if any; category
+    /// calls always try the other candidates before the rest of the policy.
+    async fn serve(
+        &self,
+        candidates: Vec<(&Arc<dyn JokeProvider>, Option<String>)>,
+        category: Option<&str>,
+    ) -> Result<ServedJoke, ProviderError> {
+        let first = if self.providers.is_empty() {
+            Err(ProviderError::NoProviders)
+        } else if candidates.is_empty() {
+            // Only category calls can have no candidates
+            Err(ProviderError::UnsupportedCategory(category.unwrap_or_default().to_string()))
+        } else {
+            self.pick(candidates.clone(), |(provider, _)| *provider).ok_or(ProviderError::NoHealthyProviders)
+        };
 
-        let joke = provider.get_random_joke().await?;
-        Ok(JokeWithProvider {
+        let (error, mut remaining) = match first {
+            Ok(((provider, category), permit)) => match self.call(provider, category.as_deref(), permit).await.0 {
+                Ok(joke) => {
+                    return Ok(ServedJoke {
+                        joke: JokeWithProvider {
+                            joke,
+                            provider: provider.id().to_string(),
+                        },
+                        fallback: None,
+                        stored: None,
+                    });
+                }
+                Err(error) => {
+                    let remaining = candidates.into_iter()
+                        .filter(|(candidate, _)| !Arc::ptr_eq(candidate, provider))
+                        .collect::<Vec<_>>();
+                    (error, remaining)
+                }
+            },
+            Err(error) => (error, Vec::new()),
+        };
+
+        // Category calls try every other supporting provider before the configured steps
+        let mut steps = self.fallback.steps.clone();
+        if category.is_some() {
+            steps.retain(|step| *step != FallbackStep::OtherProvider);
+            steps.insert(0, FallbackStep::OtherProvider);
+        }
+
+        for &step in &steps {
+            match step {
+                FallbackStep::OtherProvider => {
+                    while let Some(((provider, category), permit)) = self.pick(remaining.clone(), |(provider, _)| *provider) {
+                        remaining.retain(|(candidate, _)| !Arc::ptr_eq(candidate, provider));
+                        match self.call(provider, category.as_deref(), permit).await.0 {
+                            Ok(joke) => {
+                                let joke = JokeWithProvider {
+                                    joke,
+                                    provider: provider.id().to_string(),
+                                };
+                                return Ok(self.served_by_fallback(joke, step, error));
+                            }
+                            Err(e) => eprintln!("Fallback provider {} failed: {}", provider.name(), e),
+                        }
+                    }
+                }
+                FallbackStep::Cache => match &self.cache {
+                    Some(cache) => match cache.cached_joke(category).await {
+                        Ok(Some(stored)) => {
+                            let served = self.served_by_fallback(stored.clone().into(), step, error);
+                            return Ok(ServedJoke {
+                                stored: Some(stored),
+                                ..served
+                            });
+                        }
+                        Ok(None) => eprintln!("No cached joke available for fallback"),
+                        Err(e) => eprintln!("Failed to read cached joke for fallback: {}", e),
+                    },
+                    None => eprintln!("Cache fallback is configured but no joke cache is set"),
+                },
+                FallbackStep::AnyCategory if category.is_some() => {
+                    let mut remaining: Vec<_> = self.providers.iter().collect();
+                    while let Some((provider, permit)) = self.pick(remaining.clone(), |provider| *provider) {
+                        remaining.retain(|candidate| !Arc::ptr_eq(candidate, provider));
+                        match self.call(provider, None, permit).await.0 {
+                            Ok(joke) => {
+                                let joke = JokeWithProvider {
+                                    joke,
+                                    provider: provider.id().to_string(),
+                                };
+                                return Ok(self.served_by_fallback(joke, step, error));
+                            }
+                            Err(e) => eprintln!("Fallback provider {} failed: {}", provider.name(), e),
+                        }
+                    }
+                }
+                FallbackStep::AnyCategory => {}
+            }
+        }
+
+        if !steps.is_empty() {
+            eprintln!("Fallback policy exhausted: {}", error);
+        }
+        self.fallback_stats.record(None);
+        Err(error)
+    }
+
+    fn served_by_fallback(&self, joke: JokeWithProvider, step: FallbackStep, error: ProviderError) -> ServedJoke {
+        eprintln!("Served joke from {} via {:?} fallback after: {}", joke.provider, step, error);
+        self.fallback_stats.record(Some(step));
+        ServedJoke {
             joke,
-            provider: provider.base_url().to_string(),
-        })
+            fallback: Some(FallbackUsed { step, error }),
+            stored: None,
+        }
     }
 
-    /// Get a joke by category from any provider that supports it
-    pub async fn get_joke_by_category(&self, category: &str) -> Result<JokeWithProvider, Box<dyn std::error::Error + Send + Sync>> {
-        let providers_with_category: Vec<_> = self.providers.iter()
-            .filter(|p| p.get_supported_categories().iter()
-                .any(|cat| cat.to_lowercase().contains(&category.to_lowercase())))
-            .collect();
+    /// Calls served by each fallback step, and calls that failed despite the policy
+    pub fn fallback_counts(&self) -> FallbackCounts {
+        self.fallback_stats.counts()
+    }
 
-        if providers_with_category.is_empty() {
-            // Fallback to random joke if no provider supports the category
-            return self.get_random_joke().await;
-        }
+    /// Get a random joke from a provider chosen by the selection strategy
+    pub async fn get_random_joke(&self) -> Result<ServedJoke, ProviderError> {
+        let candidates = self.providers.iter().map(|provider| (provider, None)).collect();
+        self.serve(candidates, None).await
+    }
 
-        let mut rng = rand::thread_rng();
-        let index = rng.gen_range(0..providers_with_category.len());
-        let provider = providers_with_category[index];
+    /// Get a joke from a specific provider, regardless of its circuit state
+    pub async fn get_joke_from_provider(&self, provider_id: &str) -> Result<JokeWithProvider, ProviderError> {
+        let provider = self.provider(provider_id)
+            .ok_or_else(|| ProviderError::UnknownProvider(provider_id.to_string()))?;
 
-        let joke = provider.get_joke_by_category(category).await?;
+        let joke = self.call(provider, None, None).await.0?;
         Ok(JokeWithProvider {
             joke,
-            provider: provider.base_url().to_string(),
+            provider: provider.id().to_string(),
         })
     }
 
-    /// Get multiple jokes from random providers in parallel
-    pub async fn get_multiple_jokes(&self, count: usize) -> Result<Vec<JokeWithProvider>, Box<dyn std::error::Error + Send + Sync>> {
+    /// Get a joke in a canonical category, e.g. 'programming', from a healthy provider that
+    /// supports it, trying each other supporting provider when one fails. Fails with
+    /// `UnsupportedCategory` when no provider supports it, unless the fallback policy
+    /// includes `FallbackStep::AnyCategory`.
+    pub async fn get_joke_by_category(&self, category: &str) -> Result<ServedJoke, ProviderError> {
+        // Ask each provider for its own spelling of the category
+        let candidates: Vec<_> = self.providers.iter()
+            .filter_map(|provider| provider.get_supported_categories().into_iter()
+                .find(|supported| mapped_category(provider.id(), supported) == Some(category))
+                .map(|supported| (provider, Some(supported))))
+            .collect();
+
+        self.serve(candidates, Some(category)).await
+    }
+
+    /// Get multiple jokes in parallel from providers chosen by the selection strategy.
+    /// Individual provider failures do not fail the call; they are collected in the result.
+    pub async fn get_multiple_jokes(&self, count: usize) -> Result<MultipleJokes, ProviderError> {
         use futures::future::join_all;
-        
-        let futures = (0..count)
-            .map(|_| self.get_random_joke())
+
+        if self.providers.is_empty() {
+            return Err(ProviderError::NoProviders);
+        }
+
+        // Stop picking once every circuit is open or waiting on its probe
+        let providers: Vec<_> = (0..count).map_while(|_| self.random_provider().ok()).collect();
+        if providers.is_empty() && count > 0 {
+            return Err(ProviderError::NoHealthyProviders);
+        }
+
+        let futures = providers.into_iter()
+            .map(|(provider, permit)| async move {
+                let (result, latency_ms) = self.call(provider, None, permit).await;
+                (provider, result, latency_ms)
+            })
             .collect::<Vec<_>>();
-            
+
         let results = join_all(futures).await;
-        
-        let jokes = results
-            .into_iter()
-            .filter_map(|result| match result {
-                Ok(joke) => Some(joke),
-                Err(e) => {
-                    eprintln!("Failed to get joke: {}", e);
-                    None
+
+        let mut jokes = Vec::new();
+        let mut failures = Vec::new();
+        for (provider, result, latency_ms) in results {
+            match result {
+                Ok(joke) => jokes.push(JokeWithProvider {
+                    joke,
+                    provider: provider.id().to_string(),
+                }),
+                Err(error) => {
+                    let kind = FailureKind::from(&error);
+                    eprintln!("Failed to get joke from {} ({:?}, {}ms): {}", provider.name(), kind, latency_ms, error);
+                    failures.push(ProviderFailure {
+                        provider: provider.id().to_string(),
+                        provider_name: provider.name().to_string(),
+                        kind,
+                        status: error.status(),
+                        error: error.to_string(),
+                        latency_ms,
+                    });
                 }
-            })
-            .collect();
-        
-        Ok(jokes)
+            }
+        }
+
+        Ok(MultipleJokes { jokes, failures })
     }
 
-    /// List all available providers
+    /// List all providers, enabled ones first
     pub fn get_providers(&self) -> Vec<ProviderInfo> {
-        self.providers.iter().map(|provider| ProviderInfo {
-            name: provider.name().to_string(),
-            base_url: provider.base_url().to_string(),
-            categories: provider.get_supported_categories(),
+        let enabled = self.providers.iter().map(|provider| (provider, true));
+        let disabled = self.disabled.iter().map(|provider| (provider, false));
+
+        enabled.chain(disabled).map(|(provider, enabled)| {
+            let health = self.breaker(provider).map(|breaker| breaker.snapshot(provider.id(), provider.name()));
+            ProviderInfo {
+                id: provider.id().to_string(),
+                name: provider.name().to_string(),
+                base_url: provider.base_url().to_string(),
+                categories: provider.get_supported_categories(),
+                enabled,
+                success_rate: health
+                    .filter(|health| health.window_calls > 0)
+                    .map(|health| 1.0 - health.error_rate),
+            }
         }).collect()
     }
 
-    /// Get all available categories across all providers
+    /// Get the canonical categories supported by at least one enabled provider, in taxonomy order
     pub fn get_all_categories(&self) -> Vec<String> {
-        let mut categories = std::collections::HashSet::new();
-
-        for provider in &self.providers {
-            for category in provider.get_supported_categories() {
-                categories.insert(category);
-            }
-        }
-
-        let mut result: Vec<_> = categories.into_iter().collect();
-        result.sort();
-        result
+        CANONICAL_CATEGORIES.iter()
+            .filter(|canonical| self.providers.iter().any(|provider| supports(provider, canonical)))
+            .map(|canonical| canonical.to_string())
+            .collect()
     }
 }
 
@@ -124,9 +413,93 @@ pub struct JokeWithProvider {
     pub provider: String,
 }
 
+/// Jokes fetched by `get_multiple_jokes` along with the calls that failed
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MultipleJokes {
+    pub jokes: Vec<JokeWithProvider>,
+    pub failures: Vec<ProviderFailure>,
+}
+
+/// A failed call to a provider
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ProviderFailure {
+    /// Provider id, as stored in the jokes.provider column
+    pub provider: String,
+    /// Human-readable provider name
+    pub provider_name: String,
+    pub kind: FailureKind,
+    /// HTTP status code, for `FailureKind::HttpStatus` and `FailureKind::RateLimited`
+    pub status: Option<u16>,
+    pub error: String,
+    /// Time spent on the call before it failed
+    pub latency_ms: u64,
+}
+
+/// Broad category of a provider failure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureKind {
+    /// Connection, DNS or TLS problems
+    Network,
+    /// The provider did not answer in time
+    Timeout,
+    /// The provider answered 429 Too Many Requests
+    RateLimited,
+    /// The provider answered with another non-success status
+    HttpStatus,
+    /// The response body could not be decoded
+    Decode,
+    /// The provider answered with an error in the body
+    Upstream,
+    /// The provider does not serve the requested category
+    UnsupportedCategory,
+    /// The provider has no joke matching the request
+    NoMatchingJoke,
+    /// The provider answered but the joke had no text
+    EmptyContent,
+    /// Anything else
+    Other,
+}
+
+impl From<&ProviderError> for FailureKind {
+    fn from(error: &ProviderError) -> Self {
+        match error {
+            ProviderError::Network(_) => FailureKind::Network,
+            ProviderError::Timeout => FailureKind::Timeout,
+            ProviderError::RateLimited { .. } => FailureKind::RateLimited,
+            ProviderError::HttpStatus { .. } => FailureKind::HttpStatus,
+            ProviderError::Decode(_) => FailureKind::Decode,
+            ProviderError::Upstream(_) => FailureKind::Upstream,
+            ProviderError::UnsupportedCategory(_) => FailureKind::UnsupportedCategory,
+            ProviderError::NoMatchingJoke(_) => FailureKind::NoMatchingJoke,
+            ProviderError::EmptyJoke => FailureKind::EmptyContent,
+            ProviderError::NoProviders
+            | ProviderError::NoHealthyProviders
+            | ProviderError::UnknownProvider(_) => FailureKind::Other,
+        }
+    }
+}
+
+/// Whether one of the provider's categories maps onto the canonical `category`
+fn supports(provider: &Arc<dyn JokeProvider>, category: &str) -> bool {
+    provider.get_supported_categories().iter()
+        .any(|supported| mapped_category(provider.id(), supported) == Some(category))
+}
+
+fn breakers_for(providers: &[Arc<dyn JokeProvider>], config: &CircuitBreakerConfig) -> Arc<HashMap<String, CircuitBreaker>> {
+    Arc::new(providers.iter()
+        .map(|provider| (provider.id().to_string(), CircuitBreaker::new(config.clone())))
+        .collect())
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ProviderInfo {
+    pub id: String,
     pub name: String,
     pub base_url: String,
+    /// Categories in the provider's own naming
     pub categories: Vec<String>,
+    pub enabled: bool,
+    /// Share of successful calls in the circuit breaker's rolling window, `None` before any call
+    pub success_rate: Option<f64>,
 }
\ No newline at end of file
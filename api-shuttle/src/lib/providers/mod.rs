@@ -1,4 +1,9 @@
 pub mod types;
+pub mod categories;
+pub mod config;
+pub mod error;
+pub mod http;
+pub mod models;
 pub mod jokes_api;
 pub mod dad_jokes;
 pub mod chuck_norris;
@@ -6,25 +11,26 @@ pub mod official_joke;
 pub mod sv443_joke;
 pub mod jokes_one;
 pub mod manager;
+pub mod registry;
+pub mod retry;
+pub mod health;
+pub mod selection;
+pub mod fallback;
 
 pub use types::*;
+pub use error::ProviderError;
+pub use categories::{canonical_category, CANONICAL_CATEGORIES};
+pub use config::{ProviderConfig, ProvidersConfig, PROVIDER_IDS};
+pub use http::{HttpClient, HttpClientConfig};
+pub use registry::ProviderRegistry;
 pub use jokes_api::JokesApiProvider;
 pub use dad_jokes::DadJokesProvider;
 pub use chuck_norris::ChuckNorrisProvider;
 pub use official_joke::OfficialJokeProvider;
 pub use sv443_joke::Sv443JokeProvider;
 pub use jokes_one::JokesOneProvider;
-pub use manager::{JokeManager, JokeWithProvider, ProviderInfo};
-
-use std::sync::Arc;
-
-lazy_static::lazy_static! {
-    pub static ref ALL_PROVIDERS: Vec<Arc<dyn JokeProvider>> = vec![
-        Arc::new(JokesApiProvider::new()),
-        Arc::new(DadJokesProvider::new()),
-        Arc::new(ChuckNorrisProvider::new()),
-        Arc::new(OfficialJokeProvider::new()),
-        Arc::new(Sv443JokeProvider::new()),
-        Arc::new(JokesOneProvider::new(None)),
-    ];
-}
\ No newline at end of file
+pub use health::{CallPermit, CircuitBreaker, CircuitBreakerConfig, CircuitState, ProviderHealth};
+pub use fallback::{FallbackCounts, FallbackPolicy, FallbackStep, FallbackUsed, JokeCache, ServedJoke};
+pub use selection::{ProviderSelector, SelectionStrategy};
+pub use retry::{RetryPolicy, RetryingProvider};
+pub use manager::{FailureKind, JokeManager, JokeWithProvider, MultipleJokes, ProviderFailure, ProviderInfo};
@@ -0,0 +1,360 @@
+//! Response bodies of the upstream joke APIs and their conversions into `Joke`
+
+use reqwest::Response;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use super::error::ProviderError;
+use super::types::{Joke, JokeContent, JokeType};
+
+/// A joke payload from an upstream API, along with that API's error payload
+pub trait UpstreamJoke: DeserializeOwned {
+    type Error: UpstreamError;
+}
+
+/// An upstream error payload. It is decoded from every response, so all fields are optional.
+pub trait UpstreamError: DeserializeOwned {
+    /// The error described by the payload, or `None` when the response is not an error
+    fn into_error(self) -> Option<ProviderError>;
+}
+
+/// Decode a successful response, failing with the API's error payload if it sent one.
+/// Decode errors name the offending field, e.g. "setup: invalid type: null, expected a string".
+pub async fn decode<T: UpstreamJoke>(response: Response) -> Result<T, ProviderError> {
+    let body = response.bytes().await?;
+    let data: Value = serde_json::from_slice(&body)?;
+
+    if let Some(error) = T::Error::deserialize(&data).ok().and_then(UpstreamError::into_error) {
+        return Err(error);
+    }
+
+    serde_path_to_error::deserialize(&data).map_err(|e| ProviderError::Decode(e.to_string()))
+}
+
+fn missing_field(name: &str) -> ProviderError {
+    ProviderError::Decode(format!("missing field `{}`", name))
+}
+
+// JokeAPI (v2.jokeapi.dev and sv443.net)
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JokeApiType {
+    Single,
+    Twopart,
+}
+
+/// The single-type fields `joke`, and the two-part fields `setup` and `delivery`,
+/// are only present for their type
+#[derive(Debug, Deserialize)]
+pub struct JokeApiJoke {
+    pub id: u64,
+    pub r#type: JokeApiType,
+    pub category: String,
+    pub joke: Option<String>,
+    pub setup: Option<String>,
+    pub delivery: Option<String>,
+    pub safe: Option<bool>,
+    pub lang: Option<String>,
+}
+
+/// JokeAPI error code for a request no joke matches
+const JOKEAPI_NO_MATCHING_JOKE: u16 = 106;
+
+/// `{"error": true, "code": 106, "message": ..., "causedBy": [...]}`
+#[derive(Debug, Deserialize)]
+pub struct JokeApiError {
+    #[serde(default)]
+    pub error: bool,
+    pub code: Option<u16>,
+    pub message: Option<String>,
+    #[serde(rename = "causedBy", default)]
+    pub caused_by: Vec<String>,
+}
+
+impl UpstreamJoke for JokeApiJoke {
+    type Error = JokeApiError;
+}
+
+impl UpstreamError for JokeApiError {
+    fn into_error(self) -> Option<ProviderError> {
+        if !self.error {
+            return None;
+        }
+
+        let message = self.message.unwrap_or_else(|| "Unknown error".to_string());
+        let message = if self.caused_by.is_empty() {
+            message
+        } else {
+            format!("{} ({})", message, self.caused_by.join(" "))
+        };
+        Some(if self.code == Some(JOKEAPI_NO_MATCHING_JOKE) {
+            ProviderError::NoMatchingJoke(message)
+        } else {
+            ProviderError::Upstream(message)
+        })
+    }
+}
+
+impl TryFrom<JokeApiJoke> for Joke {
+    type Error = ProviderError;
+
+    fn try_from(data: JokeApiJoke) -> Result<Self, Self::Error> {
+        let (r#type, joke) = match data.r#type {
+            JokeApiType::Single => (JokeType::Single, JokeContent {
+                content: Some(data.joke.ok_or_else(|| missing_field("joke"))?),
+                setup: None,
+                punchline: None,
+            }),
+            JokeApiType::Twopart => (JokeType::Twopart, JokeContent {
+                content: None,
+                setup: Some(data.setup.ok_or_else(|| missing_field("setup"))?),
+                punchline: Some(data.delivery.ok_or_else(|| missing_field("delivery"))?),
+            }),
+        };
+
+        Ok(Joke {
+            id: Some(data.id.to_string()),
+            joke,
+            category: Some(data.category.to_lowercase()),
+            r#type,
+            safe: data.safe,
+            lang: data.lang,
+        })
+    }
+}
+
+/// JokeAPI's spelling of a supported category for request paths, e.g. 'Programming' for 'programming'
+pub fn supported_category(categories: &[String], category: &str) -> Result<String, ProviderError> {
+    let lowercase = category.to_lowercase();
+    if !categories.contains(&lowercase) {
+        return Err(ProviderError::UnsupportedCategory(category.to_string()));
+    }
+
+    let mut chars = lowercase.chars();
+    Ok(chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default())
+}
+
+// icanhazdadjoke
+
+#[derive(Debug, Deserialize)]
+pub struct DadJoke {
+    pub id: String,
+    pub joke: String,
+}
+
+/// The body repeats the HTTP status, with a message instead of a joke on failure
+#[derive(Debug, Deserialize)]
+pub struct DadJokeError {
+    pub status: Option<u16>,
+    pub message: Option<String>,
+}
+
+impl UpstreamJoke for DadJoke {
+    type Error = DadJokeError;
+}
+
+impl UpstreamError for DadJokeError {
+    fn into_error(self) -> Option<ProviderError> {
+        match self.status {
+            Some(429) => Some(ProviderError::RateLimited { retry_after: None }),
+            Some(status) if status != 200 => {
+                let message = self.message.unwrap_or_else(|| "Unknown error".to_string());
+                Some(ProviderError::Upstream(format!("{} (status {})", message, status)))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl From<DadJoke> for Joke {
+    fn from(data: DadJoke) -> Self {
+        Joke {
+            id: Some(data.id),
+            joke: JokeContent {
+                content: Some(data.joke),
+                setup: None,
+                punchline: None,
+            },
+            category: Some("dad jokes".to_string()),
+            r#type: JokeType::Single,
+            safe: None,
+            lang: None,
+        }
+    }
+}
+
+// chucknorris.io
+
+#[derive(Debug, Deserialize)]
+pub struct ChuckNorrisJoke {
+    pub id: String,
+    pub value: String,
+    #[serde(default)]
+    pub categories: Vec<String>,
+}
+
+/// `{"status": 404, "error": "Not Found", "message": ...}`
+#[derive(Debug, Deserialize)]
+pub struct ChuckNorrisError {
+    pub error: Option<String>,
+    pub message: Option<String>,
+}
+
+impl UpstreamJoke for ChuckNorrisJoke {
+    type Error = ChuckNorrisError;
+}
+
+impl UpstreamError for ChuckNorrisError {
+    fn into_error(self) -> Option<ProviderError> {
+        let error = self.error?;
+        Some(ProviderError::Upstream(self.message.unwrap_or(error)))
+    }
+}
+
+/// Jokes without a category are filed as 'uncategorized'
+impl From<ChuckNorrisJoke> for Joke {
+    fn from(data: ChuckNorrisJoke) -> Self {
+        Joke {
+            id: Some(data.id),
+            joke: JokeContent {
+                content: Some(data.value),
+                setup: None,
+                punchline: None,
+            },
+            category: Some(data.categories.first()
+                .map(|category| category.to_lowercase())
+                .unwrap_or_else(|| "uncategorized".to_string())),
+            r#type: JokeType::Single,
+            safe: None,
+            lang: None,
+        }
+    }
+}
+
+// Official Joke API
+
+#[derive(Debug, Deserialize)]
+pub struct OfficialJoke {
+    pub id: u64,
+    /// The joke's category
+    pub r#type: String,
+    pub setup: String,
+    pub punchline: String,
+}
+
+/// `{"type": "error", "message": ...}`
+#[derive(Debug, Deserialize)]
+pub struct OfficialJokeError {
+    pub r#type: Option<String>,
+    pub message: Option<String>,
+}
+
+impl UpstreamJoke for OfficialJoke {
+    type Error = OfficialJokeError;
+}
+
+/// Category endpoints answer with a list of jokes
+impl UpstreamJoke for Vec<OfficialJoke> {
+    type Error = OfficialJokeError;
+}
+
+impl UpstreamError for OfficialJokeError {
+    fn into_error(self) -> Option<ProviderError> {
+        if self.r#type.as_deref() != Some("error") {
+            return None;
+        }
+        Some(ProviderError::Upstream(self.message.unwrap_or_else(|| "Unknown error".to_string())))
+    }
+}
+
+impl From<OfficialJoke> for Joke {
+    fn from(data: OfficialJoke) -> Self {
+        Joke {
+            id: Some(data.id.to_string()),
+            joke: JokeContent {
+                content: None,
+                setup: Some(data.setup),
+                punchline: Some(data.punchline),
+            },
+            category: Some(data.r#type.to_lowercase()),
+            r#type: JokeType::Twopart,
+            safe: None,
+            lang: None,
+        }
+    }
+}
+
+// Jokes One
+
+/// Joke of the day: `{"contents": {"jokes": [{"joke": {...}}]}}`
+#[derive(Debug, Deserialize)]
+pub struct JokesOneResponse {
+    pub contents: JokesOneContents,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JokesOneContents {
+    pub jokes: Vec<JokesOneEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JokesOneEntry {
+    pub joke: JokesOneJoke,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JokesOneJoke {
+    pub id: Option<String>,
+    pub text: String,
+    pub lang: Option<String>,
+}
+
+/// `{"error": {"code": 429, "message": ...}}`
+#[derive(Debug, Deserialize)]
+pub struct JokesOneError {
+    pub error: Option<JokesOneErrorDetail>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JokesOneErrorDetail {
+    pub code: Option<u16>,
+    pub message: Option<String>,
+}
+
+impl UpstreamJoke for JokesOneResponse {
+    type Error = JokesOneError;
+}
+
+impl UpstreamError for JokesOneError {
+    fn into_error(self) -> Option<ProviderError> {
+        let error = self.error?;
+        Some(match error.code {
+            Some(429) => ProviderError::RateLimited { retry_after: None },
+            _ => ProviderError::Upstream(error.message.unwrap_or_else(|| "Unknown error".to_string())),
+        })
+    }
+}
+
+/// The entry's category is the feed it came from, e.g. 'jod', so it is not kept
+impl TryFrom<JokesOneResponse> for Joke {
+    type Error = ProviderError;
+
+    fn try_from(data: JokesOneResponse) -> Result<Self, Self::Error> {
+        let entry = data.contents.jokes.into_iter().next()
+            .ok_or_else(|| missing_field("contents.jokes[0]"))?;
+
+        Ok(Joke {
+            id: entry.joke.id,
+            joke: JokeContent {
+                content: Some(entry.joke.text),
+                setup: None,
+                punchline: None,
+            },
+            category: None,
+            r#type: JokeType::Single,
+            safe: None,
+            lang: entry.joke.lang,
+        })
+    }
+}
@@ -1,16 +1,36 @@
-use super::types::{Joke, JokeContent, JokeProvider, JokeType};
+use super::config::ProviderConfig;
+use super::error::ProviderError;
+use super::http::HttpClient;
+use super::models::{decode, OfficialJoke};
+use super::types::{Joke, JokeProvider, JokeType};
 use async_trait::async_trait;
-use reqwest::Client;
+
+/// Base URL used unless the config sets one
+const DEFAULT_BASE_URL: &str = "https://official-joke-api.appspot.com";
 
 pub struct OfficialJokeProvider {
-    client: Client,
+    client: HttpClient,
+    base_url: String,
     categories: Vec<String>,
 }
 
 impl OfficialJokeProvider {
     pub fn new() -> Self {
+        Self::from_config(&ProviderConfig::default(), &HttpClient::default())
+    }
+
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        let config = ProviderConfig {
+            base_url: Some(base_url.into()),
+            ..ProviderConfig::default()
+        };
+        Self::from_config(&config, &HttpClient::default())
+    }
+
+    pub fn from_config(config: &ProviderConfig, client: &HttpClient) -> Self {
         Self {
-            client: Client::new(),
+            client: client.for_provider(config),
+            base_url: config.base_url_or(DEFAULT_BASE_URL),
             categories: vec![
                 "general".to_string(),
                 "programming".to_string(),
@@ -21,68 +41,55 @@ impl OfficialJokeProvider {
     }
 }
 
+impl Default for OfficialJokeProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait]
 impl JokeProvider for OfficialJokeProvider {
+    fn id(&self) -> &str {
+        "officialjoke"
+    }
+
     fn name(&self) -> &str {
         "Official Joke API"
     }
 
     fn base_url(&self) -> &str {
-        "https://official-joke-api.appspot.com"
+        &self.base_url
     }
 
-    async fn get_random_joke(&self) -> Result<Joke, Box<dyn std::error::Error + Send + Sync>> {
+    async fn get_random_joke(&self) -> Result<Joke, ProviderError> {
         let url = format!("{}/random_joke", self.base_url());
-        let response = self.client.get(&url).send().await?;
-        let data: serde_json::Value = response.json().await?;
-
-        Ok(Joke {
-            id: data["id"].as_u64().map(|id| id.to_string()),
-            joke: JokeContent {
-                content: None,
-                setup: data["setup"].as_str().map(|s| s.to_string()),
-                punchline: data["punchline"].as_str().map(|s| s.to_string()),
-            },
-            category: data["type"].as_str().map(|s| s.to_lowercase()),
-            r#type: JokeType::Twopart,
-            safe: None,
-            lang: None,
-        })
+        let response = ProviderError::check_status(self.client.get(&url).send().await?)?;
+        let data: OfficialJoke = decode(response).await?;
+
+        Joke::from(data).with_content()
     }
 
-    async fn get_joke_by_category(&self, category: &str) -> Result<Joke, Box<dyn std::error::Error + Send + Sync>> {
-        let valid_category = if self.categories.contains(&category.to_lowercase()) {
-            category.to_lowercase()
-        } else {
-            "general".to_string()
-        };
+    async fn get_joke_by_category(&self, category: &str) -> Result<Joke, ProviderError> {
+        let valid_category = category.to_lowercase();
+        if !self.categories.contains(&valid_category) {
+            return Err(ProviderError::UnsupportedCategory(category.to_string()));
+        }
 
         let url = format!("{}/jokes/{}/random", self.base_url(), valid_category);
-        let response = self.client.get(&url).send().await?;
-        let data: serde_json::Value = response.json().await?;
+        let response = ProviderError::check_status(self.client.get(&url).send().await?)?;
+        let data: Vec<OfficialJoke> = decode(response).await?;
 
         // API returns an array, so take the first joke
-        let joke_data = if data.is_array() {
-            data.as_array().and_then(|arr| arr.first()).unwrap_or(&data)
-        } else {
-            &data
-        };
-
-        Ok(Joke {
-            id: joke_data["id"].as_u64().map(|id| id.to_string()),
-            joke: JokeContent {
-                content: None,
-                setup: joke_data["setup"].as_str().map(|s| s.to_string()),
-                punchline: joke_data["punchline"].as_str().map(|s| s.to_string()),
-            },
-            category: joke_data["type"].as_str().map(|s| s.to_lowercase()),
-            r#type: JokeType::Twopart,
-            safe: None,
-            lang: None,
-        })
+        let data = data.into_iter().next().ok_or(ProviderError::EmptyJoke)?;
+        Joke::from(data).with_content()
     }
 
     fn get_supported_categories(&self) -> Vec<String> {
         self.categories.clone()
     }
-}
\ No newline at end of file
+
+    fn joke_types(&self) -> Vec<JokeType> {
+        vec![JokeType::Twopart]
+    }
+}
+
@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use super::config::{ProviderConfig, ProvidersConfig, PROVIDER_IDS};
+use super::http::HttpClient;
+use super::types::JokeProvider;
+use super::{
+    ChuckNorrisProvider, DadJokesProvider, JokesApiProvider, JokesOneProvider, OfficialJokeProvider,
+    Sv443JokeProvider,
+};
+
+/// The providers built from a `ProvidersConfig`, sharing one HTTP client
+pub struct ProviderRegistry {
+    providers: Vec<Arc<dyn JokeProvider>>,
+    /// Providers turned off in the config, built only so they can be listed
+    disabled: Vec<Arc<dyn JokeProvider>>,
+    /// Selection weights keyed by provider id
+    weights: HashMap<String, u32>,
+}
+
+impl ProviderRegistry {
+    pub fn from_config(config: &ProvidersConfig, client: &HttpClient) -> Result<Self, String> {
+        config.validate()?;
+
+        let mut providers = Vec::new();
+        let mut disabled = Vec::new();
+        let mut weights = HashMap::new();
+        for id in PROVIDER_IDS {
+            let provider_config = config.get(id);
+            let provider = build(id, &provider_config, client)?;
+            if !provider_config.is_enabled() {
+                disabled.push(provider);
+                continue;
+            }
+
+            if let Some(weight) = provider_config.weight {
+                weights.insert(provider.id().to_string(), weight);
+            }
+            providers.push(provider);
+        }
+
+        Ok(Self { providers, disabled, weights })
+    }
+
+    /// The enabled providers
+    pub fn providers(&self) -> &[Arc<dyn JokeProvider>] {
+        &self.providers
+    }
+
+    /// The providers turned off in the config
+    pub fn disabled(&self) -> &[Arc<dyn JokeProvider>] {
+        &self.disabled
+    }
+
+    /// Configured weights for the 'weighted' selection strategy, by provider id
+    pub fn weights(&self) -> &HashMap<String, u32> {
+        &self.weights
+    }
+}
+
+fn build(id: &str, config: &ProviderConfig, client: &HttpClient) -> Result<Arc<dyn JokeProvider>, String> {
+    Ok(match id {
+        "jokeapi" => Arc::new(JokesApiProvider::from_config(config, client)),
+        "icanhazdadjoke" => Arc::new(DadJokesProvider::from_config(config, client)),
+        "chucknorris" => Arc::new(ChuckNorrisProvider::from_config(config, client)),
+        "officialjoke" => Arc::new(OfficialJokeProvider::from_config(config, client)),
+        "sv443" => Arc::new(Sv443JokeProvider::from_config(config, client)),
+        "jokesone" => Arc::new(JokesOneProvider::from_config(config, client)?),
+        _ => unreachable!("PROVIDER_IDS and build() list the same providers"),
+    })
+}
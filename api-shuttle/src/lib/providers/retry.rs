@@ -0,0 +1,123 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use async_trait::async_trait;
+use rand::Rng;
+use super::error::ProviderError;
+use super::types::{Joke, JokeProvider, JokeType};
+
+/// How provider calls are retried after transient failures
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled for each following retry
+    pub base_delay: Duration,
+    /// Upper bound for a single delay. A Retry-After longer than this is not waited for.
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// A policy that makes a single attempt
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Delay before retry number `retry` (starting at 0) after `error`, or `None` to give up.
+    ///
+    /// Honours the provider's Retry-After when present, otherwise uses exponential backoff
+    /// with full jitter so parallel calls do not retry in lockstep.
+    fn delay(&self, retry: u32, error: &ProviderError) -> Option<Duration> {
+        if let Some(retry_after) = error.retry_after() {
+            return (retry_after <= self.max_delay).then_some(retry_after);
+        }
+
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+        let jittered = rand::thread_rng().gen_range(0..=backoff.as_millis() as u64);
+        Some(Duration::from_millis(jittered))
+    }
+
+    /// Run `call` until it succeeds, fails with a non-retryable error or attempts run out
+    pub async fn run<F, Fut>(&self, mut call: F) -> Result<Joke, ProviderError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<Joke, ProviderError>>,
+    {
+        let mut retry = 0;
+        loop {
+            let error = match call().await {
+                Ok(joke) => return Ok(joke),
+                Err(error) => error,
+            };
+
+            if !error.is_retryable() || retry + 1 >= self.max_attempts {
+                return Err(error);
+            }
+            let Some(delay) = self.delay(retry, &error) else {
+                return Err(error);
+            };
+
+            tokio::time::sleep(delay).await;
+            retry += 1;
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Wraps a provider so every call goes through a `RetryPolicy`
+pub struct RetryingProvider {
+    inner: Arc<dyn JokeProvider>,
+    policy: RetryPolicy,
+}
+
+impl RetryingProvider {
+    pub fn new(inner: Arc<dyn JokeProvider>, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+}
+
+#[async_trait]
+impl JokeProvider for RetryingProvider {
+    fn id(&self) -> &str {
+        self.inner.id()
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn base_url(&self) -> &str {
+        self.inner.base_url()
+    }
+
+    async fn get_random_joke(&self) -> Result<Joke, ProviderError> {
+        self.policy.run(|| self.inner.get_random_joke()).await
+    }
+
+    async fn get_joke_by_category(&self, category: &str) -> Result<Joke, ProviderError> {
+        self.policy.run(|| self.inner.get_joke_by_category(category)).await
+    }
+
+    fn get_supported_categories(&self) -> Vec<String> {
+        self.inner.get_supported_categories()
+    }
+
+    fn joke_types(&self) -> Vec<JokeType> {
+        self.inner.joke_types()
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use rand::Rng;
+use super::types::{JokeProvider, JokeType};
+
+/// How `JokeManager` chooses between the providers able to serve a call
+#[derive(Debug, Clone, Default)]
+pub enum SelectionStrategy {
+    /// Every provider is equally likely
+    #[default]
+    Random,
+    /// Random, proportional to a weight per provider id. Unlisted providers weigh 1 and a
+    /// weight of 0 excludes a provider, unless every candidate weighs 0, in which case
+    /// the pick is uniform.
+    Weighted(HashMap<String, u32>),
+    /// Take turns through the providers
+    RoundRobin,
+    /// Random among the providers with the lowest recent mean latency, i.e. those at most
+    /// `LEAST_LATENCY_TOLERANCE` times slower than the fastest, so a batch is spread
+    /// between them. Providers without latency data yet are tried first so they get measured.
+    LeastLatency,
+    /// Random among providers that serve two-part jokes, falling back to any provider
+    PreferTwopart,
+}
+
+impl SelectionStrategy {
+    /// Parse a strategy name: 'random', 'weighted', 'round_robin', 'least_latency' or
+    /// 'prefer_twopart'. Weighted selection uses `weights`.
+    pub fn parse(name: &str, weights: HashMap<String, u32>) -> Result<Self, String> {
+        match name.trim().to_lowercase().replace('-', "_").as_str() {
+            "random" => Ok(SelectionStrategy::Random),
+            "weighted" => Ok(SelectionStrategy::Weighted(weights)),
+            "round_robin" => Ok(SelectionStrategy::RoundRobin),
+            "least_latency" => Ok(SelectionStrategy::LeastLatency),
+            "prefer_twopart" => Ok(SelectionStrategy::PreferTwopart),
+            other => Err(format!("Unknown provider selection strategy: {}", other)),
+        }
+    }
+}
+
+/// How much slower than the fastest provider a provider may be and still share its calls
+pub const LEAST_LATENCY_TOLERANCE: f64 = 1.5;
+
+/// Applies a `SelectionStrategy`, keeping the round-robin position between calls
+#[derive(Debug, Default)]
+pub struct ProviderSelector {
+    strategy: SelectionStrategy,
+    next: AtomicUsize,
+}
+
+impl ProviderSelector {
+    pub fn new(strategy: SelectionStrategy) -> Self {
+        Self {
+            strategy,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Index of the chosen provider in `candidates`, which must not be empty.
+    /// `latency_ms` gives a provider's recent mean latency, if known.
+    pub fn choose(
+        &self,
+        candidates: &[&Arc<dyn JokeProvider>],
+        latency_ms: impl Fn(&Arc<dyn JokeProvider>) -> Option<u64>,
+    ) -> usize {
+        match &self.strategy {
+            SelectionStrategy::Random => random_index(candidates.len()),
+            SelectionStrategy::Weighted(weights) => {
+                let weights: Vec<u32> = candidates.iter()
+                    .map(|provider| weights.get(provider.id()).copied().unwrap_or(1))
+                    .collect();
+                weighted_index(&weights).unwrap_or_else(|| random_index(candidates.len()))
+            }
+            SelectionStrategy::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed) % candidates.len(),
+            SelectionStrategy::LeastLatency => {
+                let latencies: Vec<Option<u64>> = candidates.iter().map(|provider| latency_ms(provider)).collect();
+                let fastest = latencies.iter().flatten().min().copied().unwrap_or(0);
+                let tier: Vec<usize> = if latencies.contains(&None) {
+                    (0..candidates.len()).filter(|&index| latencies[index].is_none()).collect()
+                } else {
+                    (0..candidates.len())
+                        .filter(|&index| latencies[index].is_some_and(|latency| {
+                            latency as f64 <= fastest as f64 * LEAST_LATENCY_TOLERANCE
+                        }))
+                        .collect()
+                };
+                tier[random_index(tier.len())]
+            }
+            SelectionStrategy::PreferTwopart => {
+                let twopart: Vec<usize> = (0..candidates.len())
+                    .filter(|&index| candidates[index].joke_types().contains(&JokeType::Twopart))
+                    .collect();
+                if twopart.is_empty() {
+                    random_index(candidates.len())
+                } else {
+                    twopart[random_index(twopart.len())]
+                }
+            }
+        }
+    }
+}
+
+fn random_index(len: usize) -> usize {
+    rand::thread_rng().gen_range(0..len)
+}
+
+/// Random index proportional to `weights`, or `None` when they are all zero
+fn weighted_index(weights: &[u32]) -> Option<usize> {
+    let total: u64 = weights.iter().map(|&weight| weight as u64).sum();
+    if total == 0 {
+        return None;
+    }
+
+    let mut target = rand::thread_rng().gen_range(0..total);
+    for (index, &weight) in weights.iter().enumerate() {
+        if target < weight as u64 {
+            return Some(index);
+        }
+        target -= weight as u64;
+    }
+    None
+}
@@ -1,21 +1,40 @@
-use super::types::{Joke, JokeContent, JokeProvider, JokeType};
+use super::config::ProviderConfig;
+use super::error::ProviderError;
+use super::http::HttpClient;
+use super::models::{decode, supported_category, JokeApiJoke};
+use super::types::{Joke, JokeProvider, JokeType};
 use async_trait::async_trait;
-use reqwest::Client;
-use serde_json::Value;
+
+/// Base URL used unless the config sets one
+const DEFAULT_BASE_URL: &str = "https://sv443.net/jokeapi/v2";
 
 pub struct Sv443JokeProvider {
-    client: Client,
+    client: HttpClient,
+    base_url: String,
     categories: Vec<String>,
 }
 
 impl Sv443JokeProvider {
     pub fn new() -> Self {
+        Self::from_config(&ProviderConfig::default(), &HttpClient::default())
+    }
+
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        let config = ProviderConfig {
+            base_url: Some(base_url.into()),
+            ..ProviderConfig::default()
+        };
+        Self::from_config(&config, &HttpClient::default())
+    }
+
+    pub fn from_config(config: &ProviderConfig, client: &HttpClient) -> Self {
         Self {
-            client: Client::new(),
+            client: client.for_provider(config),
+            base_url: config.base_url_or(DEFAULT_BASE_URL),
+            // Requests use safe mode, which never serves 'dark', so it is not listed
             categories: vec![
                 "programming".to_string(),
                 "miscellaneous".to_string(),
-                "dark".to_string(),
                 "pun".to_string(),
                 "spooky".to_string(),
                 "christmas".to_string(),
@@ -24,68 +43,46 @@ impl Sv443JokeProvider {
     }
 }
 
+impl Default for Sv443JokeProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait]
 impl JokeProvider for Sv443JokeProvider {
+    fn id(&self) -> &str {
+        "sv443"
+    }
+
     fn name(&self) -> &str {
         "Sv443 JokeAPI"
     }
 
     fn base_url(&self) -> &str {
-        "https://sv443.net/jokeapi/v2"
+        &self.base_url
     }
 
-    async fn get_random_joke(&self) -> Result<Joke, Box<dyn std::error::Error + Send + Sync>> {
+    async fn get_random_joke(&self) -> Result<Joke, ProviderError> {
         let url = format!("{}/joke/Any?safe-mode&type=single,twopart", self.base_url());
-        let response = self.client.get(&url).send().await?;
-        let data: Value = response.json().await?;
-        Ok(self.normalize_joke(data))
+        let response = ProviderError::check_status(self.client.get(&url).send().await?)?;
+        let data: JokeApiJoke = decode(response).await?;
+        Joke::try_from(data)?.with_content()
     }
 
-    async fn get_joke_by_category(&self, category: &str) -> Result<Joke, Box<dyn std::error::Error + Send + Sync>> {
-        let valid_category = if self.categories.contains(&category.to_lowercase()) {
-            category
-        } else {
-            "Any"
-        };
-        let url = format!("{}/joke/{}?safe-mode&type=single,twopart", self.base_url(), valid_category);
-        let response = self.client.get(&url).send().await?;
-        let data: Value = response.json().await?;
-        Ok(self.normalize_joke(data))
+    async fn get_joke_by_category(&self, category: &str) -> Result<Joke, ProviderError> {
+        let category = supported_category(&self.categories, category)?;
+        let url = format!("{}/joke/{}?safe-mode&type=single,twopart", self.base_url(), category);
+        let response = ProviderError::check_status(self.client.get(&url).send().await?)?;
+        let data: JokeApiJoke = decode(response).await?;
+        Joke::try_from(data)?.with_content()
     }
 
     fn get_supported_categories(&self) -> Vec<String> {
         self.categories.clone()
     }
-}
 
-impl Sv443JokeProvider {
-    fn normalize_joke(&self, data: Value) -> Joke {
-        if data["type"] == "single" {
-            Joke {
-                id: data["id"].as_u64().map(|id| id.to_string()),
-                joke: JokeContent {
-                    content: data["joke"].as_str().map(|s| s.to_string()),
-                    setup: None,
-                    punchline: None,
-                },
-                category: data["category"].as_str().map(|s| s.to_lowercase()),
-                r#type: JokeType::Single,
-                safe: data["safe"].as_bool(),
-                lang: data["lang"].as_str().map(|s| s.to_string()),
-            }
-        } else {
-            Joke {
-                id: data["id"].as_u64().map(|id| id.to_string()),
-                joke: JokeContent {
-                    content: None,
-                    setup: data["setup"].as_str().map(|s| s.to_string()),
-                    punchline: data["delivery"].as_str().map(|s| s.to_string()),
-                },
-                category: data["category"].as_str().map(|s| s.to_lowercase()),
-                r#type: JokeType::Twopart,
-                safe: data["safe"].as_bool(),
-                lang: data["lang"].as_str().map(|s| s.to_string()),
-            }
-        }
+    fn joke_types(&self) -> Vec<JokeType> {
+        vec![JokeType::Single, JokeType::Twopart]
     }
-}
\ No newline at end of file
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use super::error::ProviderError;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JokeContent {
@@ -17,7 +18,24 @@ pub struct Joke {
     pub lang: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Joke {
+    /// Reject jokes missing the text required for their type
+    pub fn with_content(self) -> Result<Joke, ProviderError> {
+        let present = |text: &Option<String>| text.as_deref().is_some_and(|text| !text.trim().is_empty());
+        let has_content = match self.r#type {
+            JokeType::Single => present(&self.joke.content),
+            JokeType::Twopart => present(&self.joke.setup) && present(&self.joke.punchline),
+        };
+
+        if has_content {
+            Ok(self)
+        } else {
+            Err(ProviderError::EmptyJoke)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum JokeType {
     #[serde(rename = "single")]
     Single,
@@ -25,6 +43,28 @@ pub enum JokeType {
     Twopart,
 }
 
+impl JokeType {
+    /// Value stored in the jokes.type column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JokeType::Single => "single",
+            JokeType::Twopart => "twopart",
+        }
+    }
+}
+
+impl TryFrom<String> for JokeType {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "single" => Ok(JokeType::Single),
+            "twopart" => Ok(JokeType::Twopart),
+            _ => Err(format!("Unknown joke type: {}", value)),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct JokeApiResponse {
     pub data: serde_json::Value,
@@ -32,14 +72,22 @@ pub struct JokeApiResponse {
 
 #[async_trait::async_trait]
 pub trait JokeProvider: Send + Sync {
+    /// Stable slug identifying the provider, e.g. 'jokeapi'. Stored as a joke's
+    /// `provider` and unaffected by the configured `base_url`.
+    fn id(&self) -> &str;
     fn name(&self) -> &str;
+    /// URL requests are sent to, which may be overridden by config
     fn base_url(&self) -> &str;
-    async fn get_random_joke(&self) -> Result<Joke, Box<dyn std::error::Error + Send + Sync>>;
-    async fn get_joke_by_category(&self, _category: &str) -> Result<Joke, Box<dyn std::error::Error + Send + Sync>> {
+    async fn get_random_joke(&self) -> Result<Joke, ProviderError>;
+    async fn get_joke_by_category(&self, _category: &str) -> Result<Joke, ProviderError> {
         // Default implementation falls back to random joke
         self.get_random_joke().await
     }
     fn get_supported_categories(&self) -> Vec<String> {
         vec![]
     }
+    /// Joke types this provider returns
+    fn joke_types(&self) -> Vec<JokeType> {
+        vec![JokeType::Single]
+    }
 }
\ No newline at end of file
@@ -4,12 +4,17 @@ use sqlx::PgPool;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 use std::env;
+use std::sync::Arc;
 
 mod api_doc;
+mod config;
+mod ingestion;
 mod routes;
 
 use agitated_chebyshev::db;
+use agitated_chebyshev::db::cache::DatabaseJokeCache;
 use agitated_chebyshev::lib::providers::manager::JokeManager;
+use agitated_chebyshev::lib::providers::ProviderRegistry;
 use api_doc::ApiDoc;
 use shuttle_runtime::SecretStore;
 
@@ -32,8 +37,24 @@ async fn main(
     // Run database migrations
     db::migrate().await.expect("Failed to run migrations");
 
-    // Create the joke manager
-    let joke_manager = JokeManager::with_all_providers();
+    // Create the joke manager from the configured providers, retrying transient
+    // failures, skipping providers whose circuit is open and choosing between the
+    // rest by strategy
+    let providers_config = config::providers_config(&secrets).expect("Invalid providers config");
+    let http_client = config::http_client_config(&secrets).build().expect("Invalid HTTP client config");
+    let registry = ProviderRegistry::from_config(&providers_config, &http_client).expect("Invalid providers config");
+    let selection_strategy = config::selection_strategy(&secrets, registry.weights())
+        .expect("Invalid PROVIDER_SELECTION_STRATEGY");
+    let fallback_policy = config::fallback_policy(&secrets).expect("Invalid PROVIDER_FALLBACK");
+    let joke_manager = JokeManager::from_registry(&registry)
+        .with_retry_policy(config::retry_policy(&secrets))
+        .with_circuit_breaker(config::circuit_breaker_config(&secrets))
+        .with_selection_strategy(selection_strategy)
+        .with_fallback_policy(fallback_policy)
+        .with_joke_cache(Arc::new(DatabaseJokeCache::new(db::get_pool().clone())));
+
+    // Start background ingestion for each provider
+    ingestion::spawn(&joke_manager, &secrets);
 
     let config = move |cfg: &mut ServiceConfig| {
         cfg.app_data(Data::new(joke_manager.clone()))
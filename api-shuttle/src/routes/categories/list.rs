@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+use actix_web::{get, web, HttpResponse, Responder};
+use serde::Serialize;
+use utoipa::ToSchema;
+use agitated_chebyshev::db;
+use agitated_chebyshev::lib::providers::categories::mapped_category;
+use agitated_chebyshev::lib::providers::manager::JokeManager;
+use agitated_chebyshev::lib::providers::CANONICAL_CATEGORIES;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CategoriesResponse {
+    /// Categories served by an enabled provider or holding stored jokes, in taxonomy order
+    categories: Vec<CategorySummary>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CategorySummary {
+    /// Canonical category name, as used by the `category` filters
+    name: String,
+    /// Number of stored jokes in the category
+    joke_count: i64,
+    /// Ids of the enabled providers serving the category
+    providers: Vec<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/categories",
+    tag = "categories",
+    responses(
+        (status = 200, description = "Canonical categories with their stored joke counts and providers", body = CategoriesResponse),
+        (status = 500, description = "Database error")
+    )
+)]
+#[get("")]
+pub async fn list_categories(joke_manager: web::Data<JokeManager>) -> impl Responder {
+    let counts: HashMap<String, i64> = match db::jokes::category_counts(db::get_pool()).await {
+        Ok(counts) => counts.into_iter().collect(),
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            }));
+        }
+    };
+
+    let providers: Vec<_> = joke_manager.get_providers().into_iter().filter(|provider| provider.enabled).collect();
+    let supported = joke_manager.get_all_categories();
+
+    let categories = CANONICAL_CATEGORIES
+        .iter()
+        .filter(|category| supported.iter().any(|supported| supported == *category) || counts.contains_key(**category))
+        .map(|category| CategorySummary {
+            name: category.to_string(),
+            joke_count: counts.get(*category).copied().unwrap_or(0),
+            providers: providers
+                .iter()
+                .filter(|provider| provider.categories.iter()
+                    .any(|own| mapped_category(&provider.id, own) == Some(category)))
+                .map(|provider| provider.id.clone())
+                .collect(),
+        })
+        .collect();
+
+    HttpResponse::Ok().json(CategoriesResponse { categories })
+}
@@ -0,0 +1,10 @@
+pub mod list;
+
+use actix_web::web;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/categories")
+            .service(list::list_categories)
+    );
+}
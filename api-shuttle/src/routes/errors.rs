@@ -0,0 +1,29 @@
+use actix_web::http::header::RETRY_AFTER;
+use actix_web::HttpResponse;
+use agitated_chebyshev::lib::providers::ProviderError;
+
+/// Map a provider error to the response sent to API clients
+pub fn provider_error_response(error: &ProviderError) -> HttpResponse {
+    let mut response = match error {
+        ProviderError::Timeout => HttpResponse::GatewayTimeout(),
+        ProviderError::RateLimited { retry_after } => {
+            let mut response = HttpResponse::TooManyRequests();
+            if let Some(delay) = retry_after {
+                response.insert_header((RETRY_AFTER, delay.as_secs().to_string()));
+            }
+            response
+        }
+        ProviderError::Network(_)
+        | ProviderError::HttpStatus { .. }
+        | ProviderError::Decode(_)
+        | ProviderError::Upstream(_)
+        | ProviderError::EmptyJoke => HttpResponse::BadGateway(),
+        ProviderError::UnsupportedCategory(_) => HttpResponse::BadRequest(),
+        ProviderError::UnknownProvider(_) | ProviderError::NoMatchingJoke(_) => HttpResponse::NotFound(),
+        ProviderError::NoProviders | ProviderError::NoHealthyProviders => HttpResponse::ServiceUnavailable(),
+    };
+
+    response.json(serde_json::json!({
+        "error": error.to_string()
+    }))
+}
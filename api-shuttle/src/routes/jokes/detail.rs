@@ -0,0 +1,43 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use sqlx::types::Uuid;
+use agitated_chebyshev::db;
+use super::random::JokeDetail;
+
+#[utoipa::path(
+    get,
+    path = "/jokes/{id}",
+    tag = "jokes",
+    params(
+        ("id" = String, Path, description = "Database UUID of the joke")
+    ),
+    responses(
+        (status = 200, description = "The stored joke", body = JokeDetail),
+        (status = 400, description = "Malformed joke id"),
+        (status = 404, description = "No joke with this id"),
+        (status = 500, description = "Database error")
+    )
+)]
+#[get("/{id}")]
+pub async fn get_joke(path: web::Path<String>) -> impl Responder {
+    let id = match Uuid::parse_str(&path) {
+        Ok(id) => id,
+        Err(_) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid joke id, expected a UUID"
+            }));
+        }
+    };
+
+    match db::jokes::get(db::get_pool(), id).await {
+        Ok(Some(joke)) => HttpResponse::Ok().json(JokeDetail::from(joke)),
+        Ok(None) => HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Joke not found"
+        })),
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            }))
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use sqlx::types::Uuid;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use agitated_chebyshev::lib::providers::{FallbackStep, FallbackUsed, JokeManager, ServedJoke};
+use agitated_chebyshev::db;
+use agitated_chebyshev::db::jokes::StoredJoke;
+use crate::routes::errors::provider_error_response;
+use super::random::JokeContent;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FreshJokeParams {
+    /// Only fetch a joke in this canonical category, e.g. 'programming'
+    category: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FreshJokeResponse {
+    /// The joke as stored: saved from the provider, or the stored row the cache served
+    joke: FreshJoke,
+    /// Set when the first provider chosen failed and the fallback policy served the joke
+    fallback: Option<FallbackDetail>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FreshJoke {
+    /// Database UUID the joke is stored under
+    #[schema(value_type = String)]
+    id: Uuid,
+    /// Canonical category of the joke (may be null)
+    category: Option<String>,
+    /// Category as the source provider named it (may be null)
+    provider_category: Option<String>,
+    /// Type of joke: 'single' or 'twopart'
+    r#type: String,
+    /// Joke content
+    content: JokeContent,
+    /// Whether the joke is considered safe/SFW
+    safe: bool,
+    /// Language code
+    lang: String,
+    /// Source provider id
+    provider: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FallbackDetail {
+    /// Fallback step that served the joke: 'other_provider', 'cache' or 'any_category'
+    #[schema(value_type = String)]
+    step: FallbackStep,
+    /// Error from the provider that was chosen first
+    error: String,
+}
+
+impl From<FallbackUsed> for FallbackDetail {
+    fn from(fallback: FallbackUsed) -> Self {
+        FallbackDetail {
+            step: fallback.step,
+            error: fallback.error.to_string(),
+        }
+    }
+}
+
+impl From<StoredJoke> for FreshJoke {
+    fn from(stored: StoredJoke) -> Self {
+        FreshJoke {
+            id: stored.id,
+            category: stored.category,
+            provider_category: stored.provider_category,
+            r#type: stored.r#type.as_str().to_string(),
+            content: stored.joke.into(),
+            safe: stored.safe,
+            lang: stored.lang,
+            provider: stored.provider,
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/jokes/fresh",
+    tag = "jokes",
+    params(
+        ("category" = Option<String>, Query, description = "Only fetch a joke in this canonical category, e.g. 'programming'")
+    ),
+    responses(
+        (status = 200, description = "Successfully fetched and saved a joke", body = FreshJokeResponse),
+        (status = 400, description = "No provider supports the category"),
+        (status = 404, description = "The provider has no joke left in the category"),
+        (status = 500, description = "Failed to save the joke"),
+        (status = 502, description = "The provider failed and the fallback policy could not serve a joke"),
+        (status = 503, description = "No healthy joke providers are available"),
+        (status = 504, description = "The provider timed out")
+    )
+)]
+#[get("/fresh")]
+pub async fn fresh_joke(
+    query: web::Query<FreshJokeParams>,
+    joke_manager: web::Data<JokeManager>,
+) -> impl Responder {
+    // Fetch a single joke live, letting the fallback policy cover a failing provider
+    let served = match &query.category {
+        Some(category) => joke_manager.get_joke_by_category(category).await,
+        None => joke_manager.get_random_joke().await,
+    };
+    let ServedJoke { joke, fallback, stored } = match served {
+        Ok(served) => served,
+        Err(e) => {
+            eprintln!("Error fetching a fresh joke: {}", e);
+            return provider_error_response(&e);
+        }
+    };
+
+    // Cached jokes are already stored; anything else is saved like retrieved jokes
+    let joke = if let Some(stored) = stored {
+        FreshJoke::from(stored)
+    } else {
+        match db::jokes::save_jokes(db::get_pool(), &[joke]).await {
+            Ok(mut outcome) => match outcome.saved.pop() {
+                Some(saved) => FreshJoke::from(saved.joke),
+                None => {
+                    let reason = outcome.skipped.pop().map(|skipped| skipped.reason).unwrap_or_default();
+                    eprintln!("Provider served an invalid joke: {}", reason);
+                    return HttpResponse::BadGateway().json(serde_json::json!({
+                        "error": format!("Provider served an invalid joke: {}", reason)
+                    }));
+                }
+            },
+            Err(e) => {
+                eprintln!("Failed to save joke: {}", e);
+                return HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": format!("Database error: {}", e)
+                }));
+            }
+        }
+    };
+
+    HttpResponse::Ok().json(FreshJokeResponse {
+        joke,
+        fallback: fallback.map(FallbackDetail::from),
+    })
+}
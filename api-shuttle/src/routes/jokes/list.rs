@@ -0,0 +1,134 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
+use sqlx::types::Uuid;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use agitated_chebyshev::db;
+use agitated_chebyshev::db::jokes::ListOptions;
+use super::random::{JokeDetail, JokeFilterParams};
+
+/// Default page size when `limit` is not specified
+const DEFAULT_LIMIT: i64 = 20;
+/// Largest page size a client can request
+const MAX_LIMIT: i64 = 100;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ListJokesParams {
+    /// Number of jokes per page (default: 20, max: 100)
+    limit: Option<i64>,
+    /// Cursor from a previous page's `next_cursor`
+    cursor: Option<String>,
+    /// Sort order by creation time: 'desc' (newest first, default) or 'asc'
+    order: Option<String>,
+    #[serde(flatten)]
+    filters: JokeFilterParams,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ListJokesResponse {
+    /// Jokes on this page
+    jokes: Vec<JokeDetail>,
+    /// Cursor for the next page, null when this is the last page
+    next_cursor: Option<String>,
+}
+
+/// Position of a row in (created_at, id) order, encoded as `<created_at micros>_<id>`
+struct Cursor {
+    created_at: DateTime<Utc>,
+    id: Uuid,
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        format!("{}_{}", self.created_at.timestamp_micros(), self.id)
+    }
+
+    fn decode(value: &str) -> Option<Self> {
+        let (micros, id) = value.split_once('_')?;
+        Some(Cursor {
+            created_at: DateTime::from_timestamp_micros(micros.parse().ok()?)?,
+            id: Uuid::parse_str(id).ok()?,
+        })
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/jokes",
+    tag = "jokes",
+    params(
+        ("limit" = Option<i64>, Query, description = "Number of jokes per page (default: 20, max: 100)"),
+        ("cursor" = Option<String>, Query, description = "Cursor from a previous page's next_cursor"),
+        ("order" = Option<String>, Query, description = "Sort order by creation time: 'desc' (default) or 'asc'"),
+        ("category" = Option<String>, Query, description = "Only return jokes in this canonical category, e.g. 'programming'"),
+        ("type" = Option<String>, Query, description = "Only return jokes of this type: 'single' or 'twopart'"),
+        ("safe" = Option<bool>, Query, description = "Only return jokes with this safe/SFW flag"),
+        ("lang" = Option<String>, Query, description = "Only return jokes in this language code"),
+        ("provider" = Option<String>, Query, description = "Only return jokes from this provider id, e.g. 'jokeapi'")
+    ),
+    responses(
+        (status = 200, description = "A page of stored jokes", body = ListJokesResponse),
+        (status = 400, description = "Invalid cursor, order or filter value"),
+        (status = 500, description = "Database error")
+    )
+)]
+#[get("")]
+pub async fn list_jokes(query: web::Query<ListJokesParams>) -> impl Responder {
+    let filters = query.filters.filters();
+    if let Err(e) = filters.validate() {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": e
+        }));
+    }
+
+    let descending = match query.order.as_deref() {
+        None | Some("desc") => true,
+        Some("asc") => false,
+        Some(_) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid order, expected 'asc' or 'desc'"
+            }));
+        }
+    };
+
+    let cursor = match query.cursor.as_deref().map(Cursor::decode) {
+        None => None,
+        Some(Some(cursor)) => Some(cursor),
+        Some(None) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid cursor"
+            }));
+        }
+    };
+
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    // Fetch one extra row to know whether there is a next page
+    let options = ListOptions {
+        limit: limit + 1,
+        after: cursor.map(|cursor| (cursor.created_at, cursor.id)),
+        descending,
+    };
+
+    let mut rows = match db::jokes::list(db::get_pool(), &filters, &options).await {
+        Ok(rows) => rows,
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            }));
+        }
+    };
+
+    let next_cursor = if rows.len() as i64 > limit {
+        rows.truncate(limit as usize);
+        rows.last().map(|last| Cursor { created_at: last.created_at, id: last.id }.encode())
+    } else {
+        None
+    };
+
+    HttpResponse::Ok().json(ListJokesResponse {
+        jokes: rows.into_iter().map(JokeDetail::from).collect(),
+        next_cursor,
+    })
+}
@@ -1,5 +1,9 @@
 pub mod retrieve;
 pub mod random;
+pub mod fresh;
+pub mod list;
+pub mod search;
+pub mod detail;
 
 use actix_web::web;
 
@@ -8,5 +12,10 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         web::scope("/jokes")
             .service(retrieve::retrieve_jokes)
             .service(random::random_joke)
+            .service(fresh::fresh_joke)
+            .service(list::list_jokes)
+            .service(search::search_jokes)
+            // Registered last so it does not shadow the static paths above
+            .service(detail::get_joke)
     );
 }
@@ -1,8 +1,53 @@
-use actix_web::{get, HttpResponse, Responder};
+use actix_web::{get, web, HttpResponse, Responder};
 use sqlx::types::Uuid;
-use serde::{Deserialize, Serialize};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
 use utoipa::ToSchema;
 use agitated_chebyshev::db;
+use agitated_chebyshev::db::filters::JokeFilters;
+use agitated_chebyshev::db::jokes::StoredJoke;
+use agitated_chebyshev::lib::providers;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RandomJokeParams {
+    #[serde(flatten)]
+    filters: JokeFilterParams,
+}
+
+/// Stored-joke filters shared by the random, list and search routes
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct JokeFilterParams {
+    /// Only return jokes in this canonical category, e.g. 'programming'
+    category: Option<String>,
+    /// Only return jokes of this type: 'single' or 'twopart'
+    r#type: Option<String>,
+    /// Only return jokes with this safe/SFW flag
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    safe: Option<bool>,
+    /// Only return jokes in this language code
+    lang: Option<String>,
+    /// Only return jokes from this provider id, e.g. 'jokeapi'
+    provider: Option<String>,
+}
+
+impl JokeFilterParams {
+    pub fn filters(&self) -> JokeFilters {
+        JokeFilters {
+            category: self.category.clone(),
+            r#type: self.r#type.clone(),
+            safe: self.safe,
+            lang: self.lang.clone(),
+            provider: self.provider.clone(),
+        }
+    }
+}
+
+/// Parses a query flag; flattened query fields reach serde as strings, not booleans
+fn deserialize_flag<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<bool>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|value| value.parse().map_err(|_| D::Error::custom(format!("invalid boolean '{}'", value))))
+        .transpose()
+}
 
 #[derive(Debug, Serialize, ToSchema)]
 pub struct RandomJokeResponse {
@@ -15,8 +60,10 @@ pub struct JokeDetail {
     /// Database UUID of the joke
     #[schema(value_type = String)]
     id: Uuid,
-    /// Category of the joke (may be null)
+    /// Canonical category of the joke (may be null)
     category: Option<String>,
+    /// Category as the source provider named it (may be null)
+    provider_category: Option<String>,
     /// Type of joke: 'single' or 'twopart'
     r#type: String,
     /// Joke content
@@ -25,7 +72,7 @@ pub struct JokeDetail {
     safe: bool,
     /// Language code
     lang: String,
-    /// Source provider URL
+    /// Source provider id
     provider: String,
 }
 
@@ -39,57 +86,65 @@ pub struct JokeContent {
     punchline: Option<String>,
 }
 
+impl From<providers::JokeContent> for JokeContent {
+    fn from(joke: providers::JokeContent) -> Self {
+        JokeContent {
+            content: joke.content,
+            setup: joke.setup,
+            punchline: joke.punchline,
+        }
+    }
+}
+
+impl From<StoredJoke> for JokeDetail {
+    fn from(stored: StoredJoke) -> Self {
+        JokeDetail {
+            id: stored.id,
+            category: stored.category,
+            provider_category: stored.provider_category,
+            r#type: stored.r#type.as_str().to_string(),
+            content: stored.joke.into(),
+            safe: stored.safe,
+            lang: stored.lang,
+            provider: stored.provider,
+        }
+    }
+}
+
 #[utoipa::path(
     get,
     path = "/jokes/random",
     tag = "jokes",
+    params(
+        ("category" = Option<String>, Query, description = "Only return jokes in this canonical category, e.g. 'programming'"),
+        ("type" = Option<String>, Query, description = "Only return jokes of this type: 'single' or 'twopart'"),
+        ("safe" = Option<bool>, Query, description = "Only return jokes with this safe/SFW flag"),
+        ("lang" = Option<String>, Query, description = "Only return jokes in this language code"),
+        ("provider" = Option<String>, Query, description = "Only return jokes from this provider id, e.g. 'jokeapi'")
+    ),
     responses(
         (status = 200, description = "Successfully retrieved a random joke", body = RandomJokeResponse),
+        (status = 400, description = "Invalid filter value"),
         (status = 404, description = "No jokes found in the database"),
         (status = 500, description = "Database error")
     )
 )]
 #[get("/random")]
-pub async fn random_joke() -> impl Responder {
-    // Get a random joke from the database
-    match sqlx::query_as::<_, (Uuid, Option<String>, serde_json::Value, Option<String>, String, bool, String, String)>(
-        r#"
-        SELECT id, external_id, joke, category, type, safe, lang, provider
-        FROM jokes
-        ORDER BY RANDOM()
-        LIMIT 1
-        "#
-    )
-    .fetch_optional(db::get_pool())
-    .await {
-        Ok(Some((id, _, joke_json, category, joke_type, safe, lang, provider))) => {
-            // Parse joke content from JSON
-            let joke_content: JokeContent = match serde_json::from_value(joke_json) {
-                Ok(content) => content,
-                Err(e) => {
-                    eprintln!("Failed to parse joke content: {}", e);
-                    return HttpResponse::InternalServerError().json(serde_json::json!({
-                        "error": "Failed to parse joke content"
-                    }));
-                }
-            };
-            
-            let joke_detail = JokeDetail {
-                id,
-                category,
-                r#type: joke_type,
-                content: joke_content,
-                safe,
-                lang,
-                provider,
-            };
-            
-            HttpResponse::Ok().json(RandomJokeResponse {
-                joke: joke_detail,
-            })
-        },
+pub async fn random_joke(query: web::Query<RandomJokeParams>) -> impl Responder {
+    let filters = query.filters.filters();
+    if let Err(e) = filters.validate() {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": e
+        }));
+    }
+
+    // Get a random joke matching the filters from the database
+    match db::jokes::random(db::get_pool(), &filters).await {
+        Ok(Some(joke)) => HttpResponse::Ok().json(RandomJokeResponse {
+            joke: joke.into(),
+        }),
         Ok(None) => {
-            // No jokes found in the database
+            // No jokes matching the filters found in the database
             HttpResponse::NotFound().json(serde_json::json!({
                 "error": "No jokes found in the database"
             }))
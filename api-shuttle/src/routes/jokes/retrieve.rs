@@ -1,10 +1,12 @@
+use std::collections::BTreeMap;
 use actix_web::{get, web, HttpResponse, Responder};
 use sqlx::types::Uuid;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
-use agitated_chebyshev::lib::providers::manager::JokeManager;
-use agitated_chebyshev::lib::providers::types::JokeType;
+use agitated_chebyshev::lib::providers::manager::{FailureKind, JokeManager, ProviderFailure};
 use agitated_chebyshev::db;
+use agitated_chebyshev::db::jokes::{self, SaveOutcome, UpsertedJoke};
+use crate::routes::errors::provider_error_response;
 
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct RetrieveJokesParams {
@@ -16,8 +18,18 @@ pub struct RetrieveJokesParams {
 pub struct JokeResponse {
     /// List of retrieved jokes
     jokes: Vec<JokeSummary>,
-    /// Number of jokes successfully saved to database
+    /// Number of jokes successfully saved to database (inserted + updated)
     saved_count: usize,
+    /// Number of jokes stored as new rows
+    inserted_count: usize,
+    /// Number of existing rows refreshed with the fetched joke
+    updated_count: usize,
+    /// Fetched jokes that were not saved because they are invalid or repeated later in the batch
+    skipped: Vec<SkippedJoke>,
+    /// Provider calls that failed, with their errors
+    failures: Vec<FailedCall>,
+    /// Counts broken down per provider
+    providers: Vec<ProviderHarvest>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -25,12 +37,89 @@ pub struct JokeSummary {
     /// Database UUID of the joke
     #[schema(value_type = String)]
     id: Uuid,
-    /// Category of the joke (may be null)
+    /// Canonical category of the joke (may be null)
     category: Option<String>,
+    /// Category as the source provider named it (may be null)
+    provider_category: Option<String>,
     /// Type of joke: 'single' or 'twopart'
     r#type: String,
-    /// Source provider URL
+    /// Source provider id
     provider: String,
+    /// Whether the joke was stored as a new row (false when an existing row was refreshed)
+    inserted: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SkippedJoke {
+    /// Source provider id
+    provider: String,
+    /// Provider's id for the joke (may be null)
+    external_id: Option<String>,
+    /// Why the joke was not saved
+    reason: String,
+}
+
+impl From<jokes::SkippedJoke> for SkippedJoke {
+    fn from(skipped: jokes::SkippedJoke) -> Self {
+        SkippedJoke {
+            provider: skipped.provider,
+            external_id: skipped.external_id,
+            reason: skipped.reason,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FailedCall {
+    /// Source provider id
+    provider: String,
+    /// Provider name
+    provider_name: String,
+    /// Error kind: 'network', 'timeout', 'rate_limited', 'http_status', 'decode', 'upstream', 'unsupported_category', 'no_matching_joke', 'empty_content' or 'other'
+    #[schema(value_type = String)]
+    kind: FailureKind,
+    /// HTTP status code for 'http_status' failures
+    status: Option<u16>,
+    /// Error returned by the provider call
+    error: String,
+    /// Time spent on the call before it failed, in milliseconds
+    latency_ms: u64,
+}
+
+impl From<ProviderFailure> for FailedCall {
+    fn from(failure: ProviderFailure) -> Self {
+        FailedCall {
+            provider: failure.provider,
+            provider_name: failure.provider_name,
+            kind: failure.kind,
+            status: failure.status,
+            error: failure.error,
+            latency_ms: failure.latency_ms,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct ProviderHarvest {
+    /// Source provider id
+    provider: String,
+    /// Jokes stored as new rows
+    inserted: usize,
+    /// Existing rows refreshed
+    updated: usize,
+    /// Invalid or repeated jokes not saved
+    skipped: usize,
+    /// Failed provider calls
+    failed: usize,
+}
+
+impl ProviderHarvest {
+    fn entry<'a>(providers: &'a mut BTreeMap<String, ProviderHarvest>, provider: &str) -> &'a mut ProviderHarvest {
+        providers.entry(provider.to_string()).or_insert_with(|| ProviderHarvest {
+            provider: provider.to_string(),
+            ..ProviderHarvest::default()
+        })
+    }
 }
 
 #[utoipa::path(
@@ -42,7 +131,8 @@ pub struct JokeSummary {
     ),
     responses(
         (status = 200, description = "Successfully retrieved and saved jokes", body = JokeResponse),
-        (status = 500, description = "Failed to retrieve jokes")
+        (status = 500, description = "Failed to save jokes"),
+        (status = 503, description = "No joke providers are configured")
     )
 )]
 #[get("/retrieve")]
@@ -55,202 +145,74 @@ pub async fn retrieve_jokes(
 
     // Get jokes in parallel
     match joke_manager.get_multiple_jokes(count).await {
-        Ok(jokes_with_providers) => {
-            // Use a HashMap to deduplicate jokes by external_id and provider
-            use std::collections::HashMap;
-            let mut joke_map: HashMap<(Option<String>, String), (usize, serde_json::Value, Option<String>, String, bool, String)> = HashMap::new();
-            let mut joke_data = Vec::new();
-            
-            // Deduplicate jokes based on external_id and provider
-            for (i, joke_with_provider) in jokes_with_providers.iter().enumerate() {
-                let joke = &joke_with_provider.joke;
-                let provider = &joke_with_provider.provider;
-                
-                // Generate a key for deduplication (external_id, provider)
-                let key = (joke.id.clone(), provider.clone());
-                
-                // Determine the type string
-                let joke_type = match joke.r#type {
-                    JokeType::Single => "single",
-                    JokeType::Twopart => "twopart",
-                };
-                
-                // Extract content for database based on joke type
-                let joke_json = match joke_type {
-                    "single" => {
-                        if let Some(content) = &joke.joke.content {
-                            // For single jokes, ensure content exists and setup/punchline are absent
-                            serde_json::json!({
-                                "content": content,
-                                // Do not include setup and punchline at all (not even as null)
-                            })
-                        } else {
-                            // If single joke has no content, this will fail the check constraint
-                            eprintln!("Warning: Single joke missing content field");
-                            continue; // Skip this joke
-                        }
-                    },
-                    "twopart" => {
-                        if let (Some(setup), Some(punchline)) = (&joke.joke.setup, &joke.joke.punchline) {
-                            // For twopart jokes, ensure setup and punchline exist and content is absent
-                            serde_json::json!({
-                                "setup": setup,
-                                "punchline": punchline,
-                                // Do not include content at all (not even as null)
-                            })
-                        } else {
-                            // If twopart joke is missing setup or punchline, this will fail the check constraint
-                            eprintln!("Warning: Twopart joke missing setup or punchline fields");
-                            continue; // Skip this joke
-                        }
-                    },
-                    _ => {
-                        eprintln!("Warning: Unknown joke type: {}", joke_type);
-                        continue; // Skip this joke
-                    }
-                };
-                
-                // Store data for this joke - we'll only keep one entry per (external_id, provider)
-                joke_map.insert(
-                    key,
-                    (
-                        i,  // Original index
-                        joke_json, 
-                        joke.category.clone(),
-                        joke_type.to_string(),
-                        joke.safe.unwrap_or(true),
-                        joke.lang.as_deref().unwrap_or("en").to_string()
-                    )
-                );
-                
-                // Store the joke data for later use in the response
-                joke_data.push((
-                    joke.category.clone(),
-                    joke_type.to_string(),
-                    provider.clone(),
-                ));
-            }
-            
-            // If no jokes to insert, return early
-            if joke_map.is_empty() {
-                return HttpResponse::Ok().json(JokeResponse {
-                    jokes: Vec::new(),
-                    saved_count: 0,
-                });
-            }
-            
-            // Start a transaction
-            let pool = db::get_pool();
-            let mut tx = match pool.begin().await {
-                Ok(tx) => tx,
-                Err(e) => {
-                    eprintln!("Failed to start database transaction: {}", e);
-                    return HttpResponse::InternalServerError().json(serde_json::json!({
-                        "error": "Database error"
-                    }));
-                }
-            };
-            
-            // Create values part and bind parameters
-            let mut values = Vec::new();
-            let mut params = Vec::new();
-            
-            // Process deduplicated jokes
-            for (idx, ((external_id, provider), (_, joke_json, category, joke_type, safe, lang))) in joke_map.iter().enumerate() {
-                // Create placeholders for this joke
-                let param_idx = idx * 7 + 1;
-                values.push(format!("(${}, ${}, ${}, ${}, ${}, ${}, ${})",
-                    param_idx, param_idx+1, param_idx+2, param_idx+3, param_idx+4, param_idx+5, param_idx+6
-                ));
-                
-                // Store parameters in order
-                params.push((
-                    external_id.clone(),
-                    provider.clone(),
-                    joke_json.clone(),
-                    category.clone(),
-                    joke_type.clone(),
-                    *safe,
-                    lang.clone()
-                ));
-            }
-            
-            // Build the complete SQL query
-            let values_clause = values.join(", ");
-            let sql = format!(
-                r#"
-                INSERT INTO jokes (external_id, joke, category, type, safe, provider, lang)
-                VALUES {}
-                ON CONFLICT (external_id, provider)
-                DO UPDATE SET 
-                    joke = EXCLUDED.joke,
-                    category = EXCLUDED.category,
-                    type = EXCLUDED.type,
-                    safe = EXCLUDED.safe,
-                    lang = EXCLUDED.lang,
-                    updated_at = CURRENT_TIMESTAMP
-                RETURNING id
-                "#,
-                values_clause
-            );
-            
-            // Create the query builder
-            let mut query_builder = sqlx::query_as::<_, (Uuid,)>(&sql);
-            
-            // Bind all parameters
-            for param in &params {
-                let (ref external_id, ref provider, ref joke_json, ref category, ref joke_type, safe, ref lang) = *param;
-                query_builder = query_builder
-                    .bind(external_id.as_deref())
-                    .bind(joke_json)
-                    .bind(category)
-                    .bind(joke_type)
-                    .bind(safe)
-                    .bind(provider)
-                    .bind(lang);
-            }
-            
-            // Execute the batch insert and get the ids
-            let result = match query_builder.fetch_all(&mut *tx).await {
-                Ok(ids) => ids,
+        Ok(fetched) => {
+            let outcome = match db::jokes::save_jokes(db::get_pool(), &fetched.jokes).await {
+                Ok(outcome) => outcome,
                 Err(e) => {
-                    eprintln!("Failed to execute batch insert: {}", e);
-                    let _ = tx.rollback().await;
+                    eprintln!("Failed to save jokes: {}", e);
                     return HttpResponse::InternalServerError().json(serde_json::json!({
                         "error": format!("Database error: {}", e)
                     }));
                 }
             };
-            
-            // Commit the transaction
-            if let Err(e) = tx.commit().await {
-                eprintln!("Failed to commit transaction: {}", e);
-                return HttpResponse::InternalServerError().json(serde_json::json!({
-                    "error": "Database error"
-                }));
+
+            if !fetched.failures.is_empty() {
+                eprintln!(
+                    "Alert: {} of {} provider calls failed while retrieving jokes",
+                    fetched.failures.len(),
+                    count
+                );
             }
-            
-            // Create joke summaries for response
-            let joke_summaries = result.iter().zip(joke_data.iter())
-                .map(|((id,), (category, joke_type, provider))| JokeSummary {
-                    id: *id,
-                    category: category.clone(),
-                    r#type: joke_type.clone(),
-                    provider: provider.clone(),
-                })
-                .collect::<Vec<_>>();
-            
-            let saved_count = joke_summaries.len();
-            HttpResponse::Ok().json(JokeResponse {
-                jokes: joke_summaries,
-                saved_count,
-            })
+
+            HttpResponse::Ok().json(JokeResponse::new(outcome, fetched.failures))
         },
         Err(e) => {
             eprintln!("Error retrieving jokes: {}", e);
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to retrieve jokes"
-            }))
+            provider_error_response(&e)
+        }
+    }
+}
+
+impl JokeResponse {
+    fn new(outcome: SaveOutcome, failures: Vec<ProviderFailure>) -> Self {
+        let mut providers: BTreeMap<String, ProviderHarvest> = BTreeMap::new();
+        for joke in &outcome.saved {
+            let harvest = ProviderHarvest::entry(&mut providers, &joke.joke.provider);
+            if joke.inserted {
+                harvest.inserted += 1;
+            } else {
+                harvest.updated += 1;
+            }
+        }
+        for joke in &outcome.skipped {
+            ProviderHarvest::entry(&mut providers, &joke.provider).skipped += 1;
+        }
+        for failure in &failures {
+            ProviderHarvest::entry(&mut providers, &failure.provider).failed += 1;
+        }
+
+        let inserted_count = outcome.saved.iter().filter(|joke| joke.inserted).count();
+        JokeResponse {
+            saved_count: outcome.saved.len(),
+            inserted_count,
+            updated_count: outcome.saved.len() - inserted_count,
+            jokes: outcome.saved.into_iter().map(JokeSummary::from).collect(),
+            skipped: outcome.skipped.into_iter().map(SkippedJoke::from).collect(),
+            failures: failures.into_iter().map(FailedCall::from).collect(),
+            providers: providers.into_values().collect(),
+        }
+    }
+}
+
+impl From<UpsertedJoke> for JokeSummary {
+    fn from(upserted: UpsertedJoke) -> Self {
+        JokeSummary {
+            id: upserted.joke.id,
+            category: upserted.joke.category,
+            provider_category: upserted.joke.provider_category,
+            r#type: upserted.joke.r#type.as_str().to_string(),
+            provider: upserted.joke.provider,
+            inserted: upserted.inserted,
         }
     }
 }
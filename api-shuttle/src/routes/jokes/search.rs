@@ -0,0 +1,93 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use agitated_chebyshev::db;
+use super::random::{JokeDetail, JokeFilterParams};
+
+/// Default number of results when `limit` is not specified
+const DEFAULT_LIMIT: i64 = 10;
+/// Largest number of results a client can request
+const MAX_LIMIT: i64 = 50;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SearchJokesParams {
+    /// Search query, supports quoted phrases, `or` and `-` exclusions
+    q: String,
+    /// Maximum number of results (default: 10, max: 50)
+    limit: Option<i64>,
+    #[serde(flatten)]
+    filters: JokeFilterParams,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchJokesResponse {
+    /// Matching jokes, best match first
+    results: Vec<SearchResult>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchResult {
+    /// The matching joke
+    joke: JokeDetail,
+    /// Relevance score, higher is better
+    rank: f32,
+    /// Joke text as HTML: escaped, with matched terms wrapped in `<b>` tags
+    snippet: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/jokes/search",
+    tag = "jokes",
+    params(
+        ("q" = String, Query, description = "Search query, supports quoted phrases, 'or' and '-' exclusions"),
+        ("limit" = Option<i64>, Query, description = "Maximum number of results (default: 10, max: 50)"),
+        ("category" = Option<String>, Query, description = "Only return jokes in this canonical category, e.g. 'programming'"),
+        ("type" = Option<String>, Query, description = "Only return jokes of this type: 'single' or 'twopart'"),
+        ("safe" = Option<bool>, Query, description = "Only return jokes with this safe/SFW flag"),
+        ("lang" = Option<String>, Query, description = "Only return jokes in this language code"),
+        ("provider" = Option<String>, Query, description = "Only return jokes from this provider id, e.g. 'jokeapi'")
+    ),
+    responses(
+        (status = 200, description = "Matching jokes ranked by relevance", body = SearchJokesResponse),
+        (status = 400, description = "Missing query or invalid filter value"),
+        (status = 500, description = "Database error")
+    )
+)]
+#[get("/search")]
+pub async fn search_jokes(query: web::Query<SearchJokesParams>) -> impl Responder {
+    let q = query.q.trim();
+    if q.is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Query parameter 'q' must not be empty"
+        }));
+    }
+
+    let filters = query.filters.filters();
+    if let Err(e) = filters.validate() {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": e
+        }));
+    }
+
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    match db::jokes::search(db::get_pool(), q, &filters, limit).await {
+        Ok(hits) => HttpResponse::Ok().json(SearchJokesResponse {
+            results: hits
+                .into_iter()
+                .map(|hit| SearchResult {
+                    joke: hit.joke.into(),
+                    rank: hit.rank,
+                    snippet: hit.snippet,
+                })
+                .collect(),
+        }),
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database error"
+            }))
+        }
+    }
+}
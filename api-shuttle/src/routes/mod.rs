@@ -1,4 +1,7 @@
+pub mod categories;
+pub mod errors;
 pub mod jokes;
+pub mod providers;
 pub mod root;
 
 use actix_web::web;
@@ -6,5 +9,7 @@ use actix_web::web;
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
   cfg
     .configure(root::configure)
-    .configure(jokes::configure);
+    .configure(jokes::configure)
+    .configure(providers::configure)
+    .configure(categories::configure);
 }
@@ -0,0 +1,108 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+use agitated_chebyshev::lib::providers::manager::JokeManager;
+use agitated_chebyshev::lib::providers::{CircuitState, FallbackCounts, ProviderHealth};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProvidersHealthResponse {
+    /// Health of every configured provider
+    providers: Vec<ProviderHealthStatus>,
+    /// How often the fallback policy was used for single-joke calls
+    fallbacks: FallbackUsage,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FallbackUsage {
+    /// Calls served by another provider after the chosen one failed
+    other_provider: u64,
+    /// Calls served from stored jokes after the providers failed
+    cache: u64,
+    /// Category calls served with a joke from any category
+    any_category: u64,
+    /// Calls that failed despite the fallback policy
+    failed: u64,
+}
+
+impl From<FallbackCounts> for FallbackUsage {
+    fn from(counts: FallbackCounts) -> Self {
+        FallbackUsage {
+            other_provider: counts.other_provider,
+            cache: counts.cache,
+            any_category: counts.any_category,
+            failed: counts.failed,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProviderHealthStatus {
+    /// Source provider id
+    provider: String,
+    /// Provider name
+    provider_name: String,
+    /// Circuit state: 'closed' (healthy), 'open' (skipped) or 'half_open' (probing)
+    #[schema(value_type = String)]
+    state: CircuitState,
+    /// Share of failed calls among the recent calls, between 0 and 1
+    error_rate: f64,
+    /// Number of recent calls the error rate and latencies are computed over
+    window_calls: usize,
+    /// Failures since the last successful call
+    consecutive_failures: u32,
+    /// Calls since startup
+    total_calls: u64,
+    /// Failed calls since startup
+    total_failures: u64,
+    /// Mean latency of recent calls in milliseconds
+    avg_latency_ms: Option<u64>,
+    /// 95th percentile latency of recent calls in milliseconds
+    p95_latency_ms: Option<u64>,
+    /// Most recent error message
+    last_error: Option<String>,
+    /// When an open circuit lets the next probe call through
+    #[schema(value_type = Option<String>)]
+    retry_at: Option<DateTime<Utc>>,
+}
+
+impl From<ProviderHealth> for ProviderHealthStatus {
+    fn from(health: ProviderHealth) -> Self {
+        ProviderHealthStatus {
+            provider: health.provider,
+            provider_name: health.provider_name,
+            state: health.state,
+            error_rate: health.error_rate,
+            window_calls: health.window_calls,
+            consecutive_failures: health.consecutive_failures,
+            total_calls: health.total_calls,
+            total_failures: health.total_failures,
+            avg_latency_ms: health.avg_latency_ms,
+            p95_latency_ms: health.p95_latency_ms,
+            last_error: health.last_error,
+            retry_at: health.retry_at,
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/providers/health",
+    tag = "providers",
+    responses(
+        (status = 200, description = "Circuit state and recent error rate and latency per provider", body = ProvidersHealthResponse)
+    )
+)]
+#[get("/health")]
+pub async fn provider_health(joke_manager: web::Data<JokeManager>) -> impl Responder {
+    let providers = joke_manager
+        .get_health()
+        .into_iter()
+        .map(ProviderHealthStatus::from)
+        .collect();
+
+    HttpResponse::Ok().json(ProvidersHealthResponse {
+        providers,
+        fallbacks: FallbackUsage::from(joke_manager.fallback_counts()),
+    })
+}
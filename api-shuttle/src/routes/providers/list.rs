@@ -0,0 +1,70 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use serde::Serialize;
+use utoipa::ToSchema;
+use agitated_chebyshev::lib::providers::categories::mapped_category;
+use agitated_chebyshev::lib::providers::manager::JokeManager;
+use agitated_chebyshev::lib::providers::ProviderInfo;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProvidersResponse {
+    /// Every provider, enabled ones first
+    providers: Vec<ProviderSummary>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProviderSummary {
+    /// Provider id, as used by the `provider` filters
+    id: String,
+    /// Provider name
+    name: String,
+    /// URL requests are sent to
+    base_url: String,
+    /// Canonical categories the provider serves, as used by the `category` filters
+    categories: Vec<String>,
+    /// Categories in the provider's own naming
+    provider_categories: Vec<String>,
+    /// Whether the provider is enabled in the config
+    enabled: bool,
+    /// Share of successful recent calls, between 0 and 1 (null before any call)
+    success_rate: Option<f64>,
+}
+
+impl From<ProviderInfo> for ProviderSummary {
+    fn from(info: ProviderInfo) -> Self {
+        let mut categories: Vec<String> = Vec::new();
+        for canonical in info.categories.iter().filter_map(|category| mapped_category(&info.id, category)) {
+            if !categories.iter().any(|category| category == canonical) {
+                categories.push(canonical.to_string());
+            }
+        }
+
+        ProviderSummary {
+            id: info.id,
+            name: info.name,
+            base_url: info.base_url,
+            categories,
+            provider_categories: info.categories,
+            enabled: info.enabled,
+            success_rate: info.success_rate,
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/providers",
+    tag = "providers",
+    responses(
+        (status = 200, description = "Every configured provider with its categories and recent success rate", body = ProvidersResponse)
+    )
+)]
+#[get("")]
+pub async fn list_providers(joke_manager: web::Data<JokeManager>) -> impl Responder {
+    let providers = joke_manager
+        .get_providers()
+        .into_iter()
+        .map(ProviderSummary::from)
+        .collect();
+
+    HttpResponse::Ok().json(ProvidersResponse { providers })
+}
@@ -0,0 +1,12 @@
+pub mod health;
+pub mod list;
+
+use actix_web::web;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/providers")
+            .service(list::list_providers)
+            .service(health::provider_health)
+    );
+}
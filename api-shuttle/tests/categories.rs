@@ -0,0 +1,38 @@
+//! Mapping provider categories onto the canonical taxonomy.
+
+use agitated_chebyshev::lib::providers::categories::{canonical_category, is_canonical, CATEGORY_MAPPINGS};
+use agitated_chebyshev::lib::providers::CANONICAL_CATEGORIES;
+
+#[test]
+fn provider_specific_names_share_a_canonical_category() {
+    assert_eq!(canonical_category("chucknorris", "dev"), "programming");
+    assert_eq!(canonical_category("jokeapi", "programming"), "programming");
+    assert_eq!(canonical_category("icanhazdadjoke", "dad jokes"), "dad");
+    assert_eq!(canonical_category("officialjoke", "dad"), "dad");
+}
+
+#[test]
+fn matching_ignores_case() {
+    assert_eq!(canonical_category("jokeapi", "Misc"), "general");
+    assert_eq!(canonical_category("jokeapi", "Christmas"), "christmas");
+}
+
+#[test]
+fn mappings_are_per_provider() {
+    // Only Chuck Norris calls programming jokes 'dev'
+    assert_eq!(canonical_category("officialjoke", "dev"), "general");
+}
+
+#[test]
+fn unknown_categories_fall_back_to_general() {
+    assert_eq!(canonical_category("chucknorris", "uncategorized"), "general");
+    assert_eq!(canonical_category("jokeapi", "limericks"), "general");
+}
+
+#[test]
+fn mappings_point_at_canonical_categories() {
+    for (provider, category, canonical) in CATEGORY_MAPPINGS {
+        assert!(is_canonical(canonical), "{} '{}' maps to unknown '{}'", provider, category, canonical);
+    }
+    assert!(CANONICAL_CATEGORIES.windows(2).all(|pair| pair[0] < pair[1]));
+}
@@ -0,0 +1,107 @@
+//! Manager routing of category calls to the providers supporting the category.
+
+mod common;
+
+use agitated_chebyshev::lib::providers::{FallbackStep, JokeProvider, JokesOneProvider, ProviderError};
+use common::{manager, text, StubProvider};
+
+fn supporting(id: &'static str, categories: &[&str]) -> StubProvider {
+    StubProvider::working(id).with_categories(categories)
+}
+
+fn failing(id: &'static str, categories: &[&str]) -> StubProvider {
+    StubProvider::failing(id, ProviderError::Timeout).with_categories(categories)
+}
+
+#[tokio::test]
+async fn asks_for_the_provider_spelling_of_a_canonical_category() {
+    let manager = manager(vec![supporting("chucknorris", &["animal", "dev"])], "fail");
+
+    let served = manager.get_joke_by_category("programming").await.unwrap();
+
+    assert_eq!(text(&served.joke.joke), "chucknorris dev");
+    assert!(served.fallback.is_none());
+}
+
+#[tokio::test]
+async fn does_not_match_categories_by_substring() {
+    let manager = manager(vec![supporting("stub", &["party", "sports"])], "other_provider");
+
+    for category in ["art", "sport"] {
+        let error = manager.get_joke_by_category(category).await.unwrap_err();
+        assert_eq!(error, ProviderError::UnsupportedCategory(category.to_string()));
+    }
+}
+
+#[tokio::test]
+async fn never_picks_a_provider_without_category_support() {
+    let manager = manager(vec![supporting("random_only", &[]), supporting("working", &["pun"])], "other_provider");
+
+    for _ in 0..4 {
+        let served = manager.get_joke_by_category("pun").await.unwrap();
+        assert_eq!(text(&served.joke.joke), "working pun");
+        assert!(served.fallback.is_none());
+    }
+}
+
+#[test]
+fn jokes_one_serves_no_categories() {
+    // It only has a joke of the day, which would be stored under no category
+    assert!(JokesOneProvider::new(None).unwrap().get_supported_categories().is_empty());
+}
+
+#[tokio::test]
+async fn tries_other_supporting_providers_without_a_fallback_policy() {
+    let manager = manager(
+        vec![
+            failing("failing", &["pun"]),
+            supporting("unrelated", &["dark"]),
+            supporting("working", &["pun"]),
+        ],
+        "fail",
+    );
+
+    let served = manager.get_joke_by_category("pun").await.unwrap();
+
+    assert_eq!(text(&served.joke.joke), "working pun");
+    let fallback = served.fallback.unwrap();
+    assert_eq!(fallback.step, FallbackStep::OtherProvider);
+    assert_eq!(fallback.error, ProviderError::Timeout);
+}
+
+#[tokio::test]
+async fn fails_with_the_provider_error_when_every_supporting_provider_fails() {
+    let manager = manager(vec![failing("failing", &["pun"]), supporting("unrelated", &["dark"])], "fail");
+
+    let error = manager.get_joke_by_category("pun").await.unwrap_err();
+
+    assert_eq!(error, ProviderError::Timeout);
+    assert_eq!(manager.fallback_counts().failed, 1);
+}
+
+#[tokio::test]
+async fn serves_any_category_only_when_the_policy_allows_it() {
+    let error = manager(vec![supporting("stub", &["pun"])], "fail")
+        .get_joke_by_category("spooky")
+        .await
+        .unwrap_err();
+    assert_eq!(error, ProviderError::UnsupportedCategory("spooky".to_string()));
+
+    let manager = manager(vec![supporting("stub", &["pun"])], "any_category");
+    let served = manager.get_joke_by_category("spooky").await.unwrap();
+
+    assert_eq!(text(&served.joke.joke), "stub random");
+    let fallback = served.fallback.unwrap();
+    assert_eq!(fallback.step, FallbackStep::AnyCategory);
+    assert_eq!(fallback.error, ProviderError::UnsupportedCategory("spooky".to_string()));
+    assert_eq!(manager.fallback_counts().any_category, 1);
+}
+
+#[tokio::test]
+async fn random_calls_ignore_the_any_category_step() {
+    let manager = manager(vec![failing("stub", &[])], "any_category");
+
+    let error = manager.get_random_joke().await.unwrap_err();
+
+    assert_eq!(error, ProviderError::Timeout);
+}
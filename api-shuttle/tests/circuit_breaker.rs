@@ -0,0 +1,129 @@
+//! Circuit breaker state transitions.
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use agitated_chebyshev::lib::providers::{
+    CircuitBreaker, CircuitBreakerConfig, CircuitState, JokeManager, JokeProvider, ProviderError,
+};
+use common::StubProvider;
+
+fn breaker(open_duration: Duration) -> CircuitBreaker {
+    CircuitBreaker::new(CircuitBreakerConfig {
+        window_size: 4,
+        min_calls: 4,
+        failure_rate_threshold: 0.5,
+        open_duration,
+    })
+}
+
+fn state(breaker: &CircuitBreaker) -> CircuitState {
+    breaker.snapshot("example", "Example").state
+}
+
+const LATENCY: Duration = Duration::from_millis(10);
+
+#[test]
+fn opens_once_error_rate_reaches_threshold() {
+    let breaker = breaker(Duration::from_secs(60));
+
+    breaker.record(Ok(()), LATENCY);
+    breaker.record(Ok(()), LATENCY);
+    breaker.record(Err(&ProviderError::Timeout), LATENCY);
+    assert_eq!(state(&breaker), CircuitState::Closed, "below min_calls");
+
+    breaker.record(Err(&ProviderError::Timeout), LATENCY);
+    assert_eq!(state(&breaker), CircuitState::Open);
+    assert!(!breaker.is_available());
+    assert!(breaker.acquire().is_none());
+
+    let health = breaker.snapshot("example", "Example");
+    assert_eq!(health.error_rate, 0.5);
+    assert_eq!(health.consecutive_failures, 2);
+    assert!(health.retry_at.is_some());
+}
+
+#[test]
+fn half_open_probe_closes_or_reopens_the_circuit() {
+    let breaker = breaker(Duration::ZERO);
+    for _ in 0..4 {
+        breaker.record(Err(&ProviderError::Timeout), LATENCY);
+    }
+    assert_eq!(state(&breaker), CircuitState::Open);
+
+    // The cooled-down circuit hands out exactly one probe
+    let probe = breaker.acquire().unwrap();
+    assert_eq!(state(&breaker), CircuitState::HalfOpen);
+    assert!(breaker.acquire().is_none());
+
+    probe.record(Err(&ProviderError::Timeout), LATENCY);
+    assert_eq!(state(&breaker), CircuitState::Open);
+
+    breaker.acquire().unwrap().record(Ok(()), LATENCY);
+    assert_eq!(state(&breaker), CircuitState::Closed);
+    assert_eq!(breaker.snapshot("example", "Example").window_calls, 0);
+}
+
+#[test]
+fn unsupported_categories_do_not_count_as_failures() {
+    let breaker = breaker(Duration::from_secs(60));
+    for _ in 0..4 {
+        breaker.record(Err(&ProviderError::UnsupportedCategory("pun".to_string())), LATENCY);
+    }
+
+    let health = breaker.snapshot("example", "Example");
+    assert_eq!(health.state, CircuitState::Closed);
+    assert_eq!(health.total_calls, 0);
+}
+
+#[test]
+fn categories_without_a_matching_joke_do_not_count_as_failures() {
+    let breaker = breaker(Duration::from_secs(60));
+    for _ in 0..4 {
+        breaker.record(Err(&ProviderError::NoMatchingJoke("No matching joke found".to_string())), LATENCY);
+    }
+
+    let health = breaker.snapshot("example", "Example");
+    assert_eq!(health.state, CircuitState::Closed);
+    assert_eq!(health.total_calls, 0);
+}
+
+#[test]
+fn dropping_an_unrecorded_probe_frees_it() {
+    let breaker = breaker(Duration::ZERO);
+    for _ in 0..4 {
+        breaker.record(Err(&ProviderError::Timeout), LATENCY);
+    }
+
+    drop(breaker.acquire().unwrap());
+
+    assert_eq!(state(&breaker), CircuitState::HalfOpen);
+    assert!(breaker.is_available());
+    assert!(breaker.acquire().is_some());
+}
+
+#[tokio::test]
+async fn cancelled_probe_calls_do_not_wedge_the_circuit() {
+    let provider = Arc::new(StubProvider::failing("stub", ProviderError::Timeout));
+    let manager = JokeManager::new(vec![provider.clone() as Arc<dyn JokeProvider>])
+        .with_circuit_breaker(CircuitBreakerConfig {
+            window_size: 1,
+            min_calls: 1,
+            failure_rate_threshold: 0.5,
+            open_duration: Duration::ZERO,
+        });
+    manager.get_random_joke().await.unwrap_err();
+
+    // The probe call is dropped mid-flight, as when a client disconnects
+    provider.fail_with(None);
+    provider.delay_by(Duration::from_secs(10));
+    let cancelled = tokio::time::timeout(Duration::from_millis(50), manager.get_random_joke()).await;
+    assert!(cancelled.is_err());
+    assert_eq!(manager.get_health()[0].state, CircuitState::HalfOpen);
+
+    provider.delay_by(Duration::ZERO);
+    manager.get_random_joke().await.unwrap();
+    assert_eq!(manager.get_health()[0].state, CircuitState::Closed);
+}
@@ -0,0 +1,197 @@
+//! Helpers shared by the integration tests: stub providers for `JokeManager` and scratch
+//! databases on a local Postgres given by `DATABASE_URL`.
+
+// Each test binary uses only some of the helpers
+#![allow(dead_code)]
+
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use agitated_chebyshev::lib::providers::{
+    FallbackPolicy, Joke, JokeContent, JokeManager, JokeProvider, JokeType, ProviderError, SelectionStrategy,
+};
+use async_trait::async_trait;
+use sqlx::migrate::Migrator;
+use sqlx::postgres::{PgConnectOptions, PgPool};
+
+/// A single joke whose id and content are both `text`
+pub fn joke(text: &str) -> Joke {
+    Joke {
+        id: Some(text.to_string()),
+        joke: JokeContent {
+            content: Some(text.to_string()),
+            setup: None,
+            punchline: None,
+        },
+        category: None,
+        r#type: JokeType::Single,
+        safe: None,
+        lang: None,
+    }
+}
+
+/// Content of a single joke
+pub fn text(joke: &Joke) -> &str {
+    joke.joke.content.as_deref().unwrap()
+}
+
+/// Answers "<id> <category>", or "<id> random" for random calls, unless set to fail.
+/// Tests holding an `Arc` can change its behaviour between calls.
+pub struct StubProvider {
+    id: &'static str,
+    categories: Vec<String>,
+    joke_types: Vec<JokeType>,
+    error: Mutex<Option<ProviderError>>,
+    delay: Mutex<Duration>,
+}
+
+impl StubProvider {
+    pub fn working(id: &'static str) -> Self {
+        Self {
+            id,
+            categories: Vec::new(),
+            joke_types: vec![JokeType::Single],
+            error: Mutex::new(None),
+            delay: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    pub fn failing(id: &'static str, error: ProviderError) -> Self {
+        let provider = Self::working(id);
+        provider.fail_with(Some(error));
+        provider
+    }
+
+    pub fn with_categories(self, categories: &[&str]) -> Self {
+        Self {
+            categories: categories.iter().map(|category| category.to_string()).collect(),
+            ..self
+        }
+    }
+
+    /// Joke types the provider reports serving, for selection strategies (default: single)
+    pub fn with_joke_types(self, joke_types: &[JokeType]) -> Self {
+        Self {
+            joke_types: joke_types.to_vec(),
+            ..self
+        }
+    }
+
+    /// Fail every following call with `error`, or succeed again with `None`
+    pub fn fail_with(&self, error: Option<ProviderError>) {
+        *self.error.lock().unwrap() = error;
+    }
+
+    /// Wait this long before answering following calls
+    pub fn delay_by(&self, delay: Duration) {
+        *self.delay.lock().unwrap() = delay;
+    }
+}
+
+#[async_trait]
+impl JokeProvider for StubProvider {
+    fn id(&self) -> &str {
+        self.id
+    }
+
+    fn name(&self) -> &str {
+        self.id
+    }
+
+    fn base_url(&self) -> &str {
+        "https://stub.example"
+    }
+
+    async fn get_random_joke(&self) -> Result<Joke, ProviderError> {
+        self.get_joke_by_category("random").await
+    }
+
+    async fn get_joke_by_category(&self, category: &str) -> Result<Joke, ProviderError> {
+        let delay = *self.delay.lock().unwrap();
+        tokio::time::sleep(delay).await;
+
+        match self.error.lock().unwrap().clone() {
+            Some(error) => Err(error),
+            None => Ok(joke(&format!("{} {}", self.id, category))),
+        }
+    }
+
+    fn get_supported_categories(&self) -> Vec<String> {
+        self.categories.clone()
+    }
+
+    fn joke_types(&self) -> Vec<JokeType> {
+        self.joke_types.clone()
+    }
+}
+
+/// Round-robin makes the first provider the first choice
+pub fn manager(providers: Vec<StubProvider>, policy: &str) -> JokeManager {
+    let providers = providers.into_iter()
+        .map(|provider| Arc::new(provider) as Arc<dyn JokeProvider>)
+        .collect();
+    JokeManager::new(providers)
+        .with_selection_strategy(SelectionStrategy::RoundRobin)
+        .with_fallback_policy(FallbackPolicy::parse(policy).unwrap())
+}
+
+/// A freshly migrated database created next to the one in `DATABASE_URL`. It is removed
+/// when dropped, including when a test panics before calling `ScratchDatabase::drop`.
+pub struct ScratchDatabase {
+    url: String,
+    name: String,
+    pub pool: PgPool,
+}
+
+impl ScratchDatabase {
+    pub async fn create(prefix: &str) -> Self {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let admin = PgPool::connect(&url).await.expect("Failed to connect to database");
+
+        let name = format!("{}_{}_{}", prefix, std::process::id(), rand::random::<u32>());
+        sqlx::query(&format!("CREATE DATABASE {}", name)).execute(&admin).await.unwrap();
+        admin.close().await;
+
+        // Build the guard before migrating so a failed migration still removes the database
+        let options = PgConnectOptions::from_str(&url).unwrap().database(&name);
+        let pool = PgPool::connect_with(options).await.unwrap();
+        let db = Self { url, name, pool };
+        Migrator::new(Path::new("./migrations")).await.unwrap().run(&db.pool).await.unwrap();
+
+        db
+    }
+
+    /// Close the pool before the database is removed on drop
+    pub async fn drop(self) {
+        self.pool.close().await;
+    }
+}
+
+impl Drop for ScratchDatabase {
+    fn drop(&mut self) {
+        // This may run inside the test's runtime, which cannot be blocked on, so the
+        // database is removed from a runtime on its own thread
+        let (url, name) = (self.url.clone(), self.name.clone());
+        let removed = std::thread::spawn(move || {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap()
+                .block_on(async {
+                    let admin = PgPool::connect(&url).await?;
+                    sqlx::query(&format!("DROP DATABASE IF EXISTS {} WITH (FORCE)", name))
+                        .execute(&admin)
+                        .await?;
+                    admin.close().await;
+                    Ok::<_, sqlx::Error>(())
+                })
+        })
+        .join();
+
+        if !matches!(removed, Ok(Ok(()))) {
+            eprintln!("Failed to remove scratch database {}", self.name);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! The HTTP client shared by providers, against a local mock HTTP server.
+
+use std::time::Duration;
+
+use agitated_chebyshev::lib::providers::http::DEFAULT_USER_AGENT;
+use agitated_chebyshev::lib::providers::{
+    ChuckNorrisProvider, HttpClient, HttpClientConfig, JokeProvider, ProviderConfig, ProviderError,
+};
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn chuck_norris_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/jokes/random"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "abc",
+            "value": "Chuck Norris can unit test an entire application with a single assert.",
+            "categories": ["dev"]
+        })))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn provider(server: &MockServer, client: &HttpClient) -> ChuckNorrisProvider {
+    let config = ProviderConfig {
+        base_url: Some(server.uri()),
+        ..ProviderConfig::default()
+    };
+    ChuckNorrisProvider::from_config(&config, client)
+}
+
+#[tokio::test]
+async fn sends_the_user_agent_and_accepts_gzip() {
+    let server = chuck_norris_server().await;
+
+    provider(&server, &HttpClient::default()).get_random_joke().await.unwrap();
+
+    let request = &server.received_requests().await.unwrap()[0];
+    assert_eq!(request.headers.get("user-agent").unwrap(), DEFAULT_USER_AGENT);
+    assert!(request.headers.get("accept-encoding").unwrap().to_str().unwrap().contains("gzip"));
+}
+
+#[tokio::test]
+async fn provider_settings_override_the_shared_client() {
+    let server = chuck_norris_server().await;
+    let client = HttpClientConfig {
+        user_agent: "jokes/1.0 (ops@example.com)".to_string(),
+        ..HttpClientConfig::default()
+    }
+    .build()
+    .unwrap();
+    let overridden = ChuckNorrisProvider::from_config(
+        &ProviderConfig {
+            base_url: Some(server.uri()),
+            user_agent: Some("chuck-client/2.0".to_string()),
+            ..ProviderConfig::default()
+        },
+        &client,
+    );
+
+    provider(&server, &client).get_random_joke().await.unwrap();
+    overridden.get_random_joke().await.unwrap();
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests[0].headers.get("user-agent").unwrap(), "jokes/1.0 (ops@example.com)");
+    assert_eq!(requests[1].headers.get("user-agent").unwrap(), "chuck-client/2.0");
+}
+
+#[tokio::test]
+async fn slow_responses_time_out() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(2)))
+        .mount(&server)
+        .await;
+    let client = HttpClientConfig {
+        timeout: Duration::from_millis(200),
+        ..HttpClientConfig::default()
+    }
+    .build()
+    .unwrap();
+
+    let error = provider(&server, &client).get_random_joke().await.unwrap_err();
+
+    assert_eq!(error, ProviderError::Timeout);
+}
+
+#[tokio::test]
+async fn requests_go_through_the_proxy() {
+    let proxy = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/jokes/random"))
+        .and(header("host", "api.chucknorris.io"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "proxied",
+            "value": "Chuck Norris doesn't need a proxy. Proxies need Chuck Norris.",
+            "categories": []
+        })))
+        .expect(1)
+        .mount(&proxy)
+        .await;
+    let client = HttpClientConfig {
+        proxy: Some(proxy.uri()),
+        ..HttpClientConfig::default()
+    }
+    .build()
+    .unwrap();
+    let provider = ChuckNorrisProvider::from_config(
+        &ProviderConfig {
+            base_url: Some("http://api.chucknorris.io".to_string()),
+            ..ProviderConfig::default()
+        },
+        &client,
+    );
+
+    let joke = provider.get_random_joke().await.unwrap();
+
+    assert_eq!(joke.id.as_deref(), Some("proxied"));
+}
+
+#[test]
+fn rejects_an_invalid_proxy() {
+    let config = HttpClientConfig {
+        proxy: Some("not a url".to_string()),
+        ..HttpClientConfig::default()
+    };
+
+    assert!(config.build().is_err());
+}
@@ -0,0 +1,313 @@
+//! Integration tests for `db::jokes` against a local Postgres.
+//!
+//! Run with `DATABASE_URL=postgresql://... cargo test --test jokes_repository -- --ignored`.
+
+mod common;
+
+use agitated_chebyshev::db::filters::JokeFilters;
+use agitated_chebyshev::db::jokes::{self, ListOptions, NewJoke, StoredJoke, UpsertedJoke};
+use agitated_chebyshev::lib::providers::{Joke, JokeContent, JokeType, JokeWithProvider};
+use common::ScratchDatabase;
+
+fn single(external_id: Option<&str>, provider: &str, category: &str, content: &str) -> JokeWithProvider {
+    JokeWithProvider {
+        joke: Joke {
+            id: external_id.map(|id| id.to_string()),
+            joke: JokeContent {
+                content: Some(content.to_string()),
+                setup: None,
+                punchline: None,
+            },
+            category: Some(category.to_string()),
+            r#type: JokeType::Single,
+            safe: None,
+            lang: None,
+        },
+        provider: provider.to_string(),
+    }
+}
+
+fn twopart(external_id: &str, provider: &str, category: &str) -> JokeWithProvider {
+    JokeWithProvider {
+        joke: Joke {
+            id: Some(external_id.to_string()),
+            joke: JokeContent {
+                content: None,
+                setup: Some("Setup".to_string()),
+                punchline: Some("Punchline".to_string()),
+            },
+            category: Some(category.to_string()),
+            r#type: JokeType::Twopart,
+            safe: Some(false),
+            lang: Some("de".to_string()),
+        },
+        provider: provider.to_string(),
+    }
+}
+
+fn new_jokes(batch: &[JokeWithProvider]) -> Vec<NewJoke> {
+    batch.iter().map(|joke| NewJoke::try_from(joke).unwrap()).collect()
+}
+
+fn stored(upserted: Vec<UpsertedJoke>) -> Vec<StoredJoke> {
+    upserted.into_iter().map(|joke| joke.joke).collect()
+}
+
+async fn assert_matches_database(db: &ScratchDatabase, returned: &[StoredJoke]) {
+    for joke in returned {
+        let stored = jokes::get(&db.pool, joke.id).await.unwrap().expect("returned id should exist");
+        assert_eq!(stored.external_id, joke.external_id);
+        assert_eq!(stored.provider, joke.provider);
+        assert_eq!(stored.category, joke.category);
+        assert_eq!(stored.r#type.as_str(), joke.r#type.as_str());
+        assert_eq!(stored.joke.content, joke.joke.content);
+    }
+}
+
+async fn count(db: &ScratchDatabase) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM jokes").fetch_one(&db.pool).await.unwrap()
+}
+
+#[tokio::test]
+#[ignore = "requires a local Postgres via DATABASE_URL"]
+async fn insert_many_returns_rows_matching_stored_jokes_with_duplicates_in_batch() {
+    let db = ScratchDatabase::create("jokes_repository").await;
+
+    let batch = vec![
+        single(Some("1"), "chucknorris", "dev", "first version"),
+        twopart("7", "jokeapi", "programming"),
+        single(Some("1"), "icanhazdadjoke", "dad jokes", "same id, other provider"),
+        single(Some("1"), "chucknorris", "food", "second version"),
+        twopart("7", "jokeapi", "programming"),
+    ];
+
+    let upserted = jokes::insert_many(&db.pool, &new_jokes(&batch)).await.unwrap();
+    assert!(upserted.iter().all(|joke| joke.inserted));
+    let returned = stored(upserted);
+
+    // One row per (external_id, provider), carrying the last version's data
+    let mut keys: Vec<_> = returned
+        .iter()
+        .map(|joke| (joke.external_id.as_deref().unwrap(), joke.provider.as_str()))
+        .collect();
+    keys.sort();
+    assert_eq!(
+        keys,
+        vec![
+            ("1", "chucknorris"),
+            ("1", "icanhazdadjoke"),
+            ("7", "jokeapi"),
+        ]
+    );
+    let chuck = returned.iter().find(|joke| joke.provider == "chucknorris").unwrap();
+    assert_eq!(chuck.category.as_deref(), Some("food"));
+    assert_eq!(chuck.provider_category.as_deref(), Some("food"));
+    assert_eq!(chuck.joke.content.as_deref(), Some("second version"));
+    let jokeapi = returned.iter().find(|joke| joke.provider == "jokeapi").unwrap();
+    assert_eq!(jokeapi.lang, "de");
+    assert!(!jokeapi.safe);
+    assert_eq!(count(&db).await, 3);
+    assert_matches_database(&db, &returned).await;
+
+    // Upserting the same batch again refreshes the same rows
+    let again = jokes::insert_many(&db.pool, &new_jokes(&batch)).await.unwrap();
+    assert!(again.iter().all(|joke| !joke.inserted));
+    let again = stored(again);
+    let mut ids: Vec<_> = returned.iter().map(|joke| joke.id).collect();
+    let mut again_ids: Vec<_> = again.iter().map(|joke| joke.id).collect();
+    ids.sort();
+    again_ids.sort();
+    assert_eq!(again_ids, ids);
+    assert_eq!(count(&db).await, 3);
+
+    db.drop().await;
+}
+
+#[tokio::test]
+#[ignore = "requires a local Postgres via DATABASE_URL"]
+async fn insert_many_keeps_every_joke_without_external_id() {
+    let db = ScratchDatabase::create("jokes_repository").await;
+
+    let batch = vec![
+        single(None, "jokesone", "general", "first"),
+        single(None, "jokesone", "general", "second"),
+    ];
+
+    let returned = stored(jokes::insert_many(&db.pool, &new_jokes(&batch)).await.unwrap());
+
+    assert_eq!(returned.len(), 2);
+    assert_eq!(count(&db).await, 2);
+    assert_matches_database(&db, &returned).await;
+
+    db.drop().await;
+}
+
+#[tokio::test]
+#[ignore = "requires a local Postgres via DATABASE_URL"]
+async fn save_jokes_skips_jokes_failing_the_check_constraint() {
+    let db = ScratchDatabase::create("jokes_repository").await;
+
+    let mut invalid = single(Some("2"), "chucknorris", "dev", "text");
+    invalid.joke.joke.content = None;
+    let batch = vec![single(Some("1"), "chucknorris", "dev", "valid"), invalid];
+
+    let outcome = jokes::save_jokes(&db.pool, &batch).await.unwrap();
+
+    assert_eq!(outcome.saved.len(), 1);
+    assert_eq!(outcome.skipped.len(), 1);
+    assert_eq!(outcome.skipped[0].external_id.as_deref(), Some("2"));
+    assert_eq!(outcome.skipped[0].reason, "Single joke missing content field");
+    assert_eq!(count(&db).await, 1);
+
+    db.drop().await;
+}
+
+#[tokio::test]
+#[ignore = "requires a local Postgres via DATABASE_URL"]
+async fn save_jokes_reports_duplicates_collapsed_within_the_batch() {
+    let db = ScratchDatabase::create("jokes_repository").await;
+
+    let batch = vec![
+        single(Some("1"), "chucknorris", "dev", "first version"),
+        single(Some("1"), "chucknorris", "dev", "second version"),
+        single(Some("1"), "icanhazdadjoke", "dad jokes", "same id, other provider"),
+    ];
+
+    let outcome = jokes::save_jokes(&db.pool, &batch).await.unwrap();
+
+    assert_eq!(outcome.saved.len() + outcome.skipped.len(), batch.len());
+    assert_eq!(outcome.skipped.len(), 1);
+    assert_eq!(outcome.skipped[0].provider, "chucknorris");
+    assert_eq!(outcome.skipped[0].reason, jokes::DUPLICATE_IN_BATCH);
+    let chuck = outcome.saved.iter().find(|joke| joke.joke.provider == "chucknorris").unwrap();
+    assert_eq!(chuck.joke.joke.content.as_deref(), Some("second version"));
+
+    db.drop().await;
+}
+
+#[tokio::test]
+#[ignore = "requires a local Postgres via DATABASE_URL"]
+async fn category_filter_matches_canonical_names_across_providers() {
+    let db = ScratchDatabase::create("jokes_repository").await;
+
+    let batch = vec![
+        single(Some("1"), "chucknorris", "dev", "chuck"),
+        twopart("2", "jokeapi", "Programming"),
+        single(Some("3"), "icanhazdadjoke", "dad jokes", "dad"),
+    ];
+    jokes::insert_many(&db.pool, &new_jokes(&batch)).await.unwrap();
+
+    let filters = JokeFilters {
+        category: Some("programming".to_string()),
+        ..JokeFilters::default()
+    };
+    let options = ListOptions { limit: 10, after: None, descending: false };
+    let mut listed: Vec<_> = jokes::list(&db.pool, &filters, &options)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|joke| (joke.provider, joke.provider_category.unwrap()))
+        .collect();
+    listed.sort();
+
+    assert_eq!(
+        listed,
+        vec![
+            ("chucknorris".to_string(), "dev".to_string()),
+            ("jokeapi".to_string(), "Programming".to_string()),
+        ]
+    );
+
+    db.drop().await;
+}
+
+#[tokio::test]
+#[ignore = "requires a local Postgres via DATABASE_URL"]
+async fn search_snippets_escape_html_in_joke_text() {
+    let db = ScratchDatabase::create("jokes_repository").await;
+
+    let batch = vec![single(Some("1"), "chucknorris", "dev", "Chuck writes <script>alert(1)</script> & \"quotes\"")];
+    jokes::insert_many(&db.pool, &new_jokes(&batch)).await.unwrap();
+
+    let hits = jokes::search(&db.pool, "chuck", &JokeFilters::default(), 10).await.unwrap();
+
+    assert_eq!(hits.len(), 1);
+    assert_eq!(
+        hits[0].snippet,
+        "<b>Chuck</b> writes &lt;script&gt;alert(1)&lt;/script&gt; &amp; &quot;quotes&quot;"
+    );
+
+    db.drop().await;
+}
+
+#[tokio::test]
+#[ignore = "requires a local Postgres via DATABASE_URL"]
+async fn category_counts_group_stored_jokes_by_canonical_category() {
+    let db = ScratchDatabase::create("jokes_repository").await;
+
+    let batch = vec![
+        single(Some("1"), "chucknorris", "dev", "chuck"),
+        twopart("2", "jokeapi", "Programming"),
+        single(Some("3"), "icanhazdadjoke", "dad jokes", "dad"),
+    ];
+    jokes::insert_many(&db.pool, &new_jokes(&batch)).await.unwrap();
+
+    let counts = jokes::category_counts(&db.pool).await.unwrap();
+
+    assert_eq!(counts, vec![("dad".to_string(), 1), ("programming".to_string(), 2)]);
+
+    db.drop().await;
+}
+
+#[test]
+fn new_joke_stores_the_canonical_category() {
+    let joke = NewJoke::try_from(&single(Some("1"), "icanhazdadjoke", "dad jokes", "text")).unwrap();
+
+    assert_eq!(joke.category.as_deref(), Some("dad"));
+    assert_eq!(joke.provider_category.as_deref(), Some("dad jokes"));
+}
+
+#[test]
+fn filters_reject_non_canonical_categories() {
+    let filters = JokeFilters {
+        category: Some("dev".to_string()),
+        ..JokeFilters::default()
+    };
+
+    assert!(filters.validate().is_err());
+}
+
+#[test]
+fn new_joke_rejects_content_that_fails_the_check_constraint() {
+    let mut missing_content = single(Some("1"), "chucknorris", "dev", "text");
+    missing_content.joke.joke.content = None;
+    assert!(NewJoke::try_from(&missing_content).is_err());
+
+    let mut missing_punchline = twopart("2", "jokeapi", "pun");
+    missing_punchline.joke.joke.punchline = None;
+    assert!(NewJoke::try_from(&missing_punchline).is_err());
+}
+
+#[tokio::test]
+#[ignore = "requires a local Postgres via DATABASE_URL"]
+async fn scratch_databases_are_removed_when_a_test_panics() {
+    let (name_tx, name_rx) = std::sync::mpsc::channel();
+    let panicked = tokio::spawn(async move {
+        let db = ScratchDatabase::create("jokes_repository").await;
+        let name: String = sqlx::query_scalar("SELECT current_database()").fetch_one(&db.pool).await.unwrap();
+        name_tx.send(name).unwrap();
+        panic!("test failed before dropping its database");
+    })
+    .await;
+    assert!(panicked.unwrap_err().is_panic());
+
+    let name = name_rx.recv().unwrap();
+    let admin = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap()).await.unwrap();
+    let remaining: i64 = sqlx::query_scalar("SELECT count(*) FROM pg_database WHERE datname = $1")
+        .bind(&name)
+        .fetch_one(&admin)
+        .await
+        .unwrap();
+
+    assert_eq!(remaining, 0);
+}
@@ -0,0 +1,104 @@
+//! Manager fallback policy when the chosen provider fails.
+
+mod common;
+
+use std::sync::Arc;
+
+use agitated_chebyshev::db::jokes::StoredJoke;
+use agitated_chebyshev::lib::providers::{FallbackStep, JokeCache, JokeContent, JokeManager, JokeType, ProviderError};
+use async_trait::async_trait;
+use common::StubProvider;
+use uuid::Uuid;
+
+/// Id of the row `StubCache` serves
+const CACHED_ID: Uuid = Uuid::from_u128(1);
+
+struct StubCache;
+
+#[async_trait]
+impl JokeCache for StubCache {
+    async fn cached_joke(&self, category: Option<&str>) -> Result<Option<StoredJoke>, String> {
+        Ok(Some(StoredJoke {
+            id: CACHED_ID,
+            external_id: Some("cached".to_string()),
+            joke: JokeContent {
+                content: Some("cached".to_string()),
+                setup: None,
+                punchline: None,
+            },
+            category: category.map(|category| category.to_string()),
+            provider_category: category.map(|category| category.to_string()),
+            r#type: JokeType::Single,
+            safe: true,
+            lang: "en".to_string(),
+            provider: "cache".to_string(),
+            created_at: chrono::Utc::now(),
+            updated_at: None,
+        }))
+    }
+}
+
+fn manager(providers: Vec<StubProvider>, policy: &str) -> JokeManager {
+    common::manager(providers, policy).with_joke_cache(Arc::new(StubCache))
+}
+
+fn failing() -> StubProvider {
+    StubProvider::failing("failing", ProviderError::Timeout)
+}
+
+fn working() -> StubProvider {
+    StubProvider::working("working")
+}
+
+#[tokio::test]
+async fn fails_with_the_provider_error_by_default() {
+    let manager = manager(vec![failing(), working()], "fail");
+
+    let error = manager.get_random_joke().await.unwrap_err();
+
+    assert_eq!(error, ProviderError::Timeout);
+    assert_eq!(manager.fallback_counts().failed, 1);
+}
+
+#[tokio::test]
+async fn tries_another_provider_before_the_cache() {
+    let manager = manager(vec![failing(), working()], "other_provider,cache");
+
+    let served = manager.get_random_joke().await.unwrap();
+
+    assert_eq!(served.joke.provider, "working");
+    assert!(served.stored.is_none());
+    let fallback = served.fallback.unwrap();
+    assert_eq!(fallback.step, FallbackStep::OtherProvider);
+    assert_eq!(fallback.error, ProviderError::Timeout);
+    assert_eq!(manager.fallback_counts().other_provider, 1);
+}
+
+#[tokio::test]
+async fn serves_a_cached_joke_when_every_provider_fails() {
+    let manager = manager(vec![failing()], "other_provider,cache");
+
+    let served = manager.get_random_joke().await.unwrap();
+
+    assert_eq!(served.joke.provider, "cache");
+    assert_eq!(served.fallback.unwrap().step, FallbackStep::Cache);
+    assert_eq!(served.stored.map(|stored| stored.id), Some(CACHED_ID));
+    assert_eq!(manager.fallback_counts().cache, 1);
+}
+
+#[tokio::test]
+async fn category_calls_try_supporting_providers_before_an_earlier_cache_step() {
+    let manager = manager(
+        vec![
+            StubProvider::failing("failing", ProviderError::Timeout).with_categories(&["pun"]),
+            StubProvider::working("working").with_categories(&["pun"]),
+        ],
+        "cache,other_provider",
+    );
+
+    let served = manager.get_joke_by_category("pun").await.unwrap();
+
+    assert_eq!(served.joke.provider, "working");
+    assert_eq!(served.fallback.unwrap().step, FallbackStep::OtherProvider);
+    assert_eq!(manager.fallback_counts().cache, 0);
+}
@@ -0,0 +1,390 @@
+//! Provider parsing against recorded upstream payloads served from a local mock HTTP
+//! server, so format drift is caught without network access.
+//!
+//! Fixtures live in `tests/fixtures/providers/<provider id>/`.
+
+use agitated_chebyshev::lib::providers::{
+    ChuckNorrisProvider, DadJokesProvider, Joke, JokeProvider, JokeType, JokesApiProvider,
+    JokesOneProvider, OfficialJokeProvider, ProviderError, Sv443JokeProvider,
+};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn fixture(name: &str) -> String {
+    let path = format!("{}/tests/fixtures/providers/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("Failed to read fixture {}: {}", path, e))
+}
+
+/// Mock server answering GET `route` with the fixture and status
+async fn serve(route: &str, status: u16, name: &str) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(route))
+        .respond_with(ResponseTemplate::new(status).set_body_raw(fixture(name), "application/json"))
+        .mount(&server)
+        .await;
+    server
+}
+
+/// Error from asking the provider for a random joke
+async fn random_error(provider: impl JokeProvider) -> ProviderError {
+    provider.get_random_joke().await.unwrap_err()
+}
+
+fn assert_single(joke: &Joke, id: Option<&str>, content: &str, category: Option<&str>) {
+    assert_eq!(joke.r#type, JokeType::Single);
+    assert_eq!(joke.id.as_deref(), id);
+    assert_eq!(joke.joke.content.as_deref(), Some(content));
+    assert_eq!(joke.joke.setup, None);
+    assert_eq!(joke.joke.punchline, None);
+    assert_eq!(joke.category.as_deref(), category);
+}
+
+fn assert_twopart(joke: &Joke, id: &str, setup: &str, punchline: &str, category: &str) {
+    assert_eq!(joke.r#type, JokeType::Twopart);
+    assert_eq!(joke.id.as_deref(), Some(id));
+    assert_eq!(joke.joke.content, None);
+    assert_eq!(joke.joke.setup.as_deref(), Some(setup));
+    assert_eq!(joke.joke.punchline.as_deref(), Some(punchline));
+    assert_eq!(joke.category.as_deref(), Some(category));
+}
+
+// JokeAPI, also served by sv443.net
+
+#[tokio::test]
+async fn jokeapi_single() {
+    let server = serve("/joke/Any", 200, "jokeapi/single.json").await;
+
+    for provider in [
+        Box::new(JokesApiProvider::with_base_url(server.uri())) as Box<dyn JokeProvider>,
+        Box::new(Sv443JokeProvider::with_base_url(server.uri())),
+    ] {
+        let joke = provider.get_random_joke().await.unwrap();
+
+        assert_single(
+            &joke,
+            Some("25"),
+            "I've got a really good UDP joke to tell you but I don't know if you'll get it.",
+            Some("programming"),
+        );
+        assert_eq!(joke.safe, Some(true));
+        assert_eq!(joke.lang.as_deref(), Some("en"));
+    }
+}
+
+#[tokio::test]
+async fn jokeapi_twopart() {
+    let server = serve("/joke/Pun", 200, "jokeapi/twopart.json").await;
+
+    for provider in [
+        Box::new(JokesApiProvider::with_base_url(server.uri())) as Box<dyn JokeProvider>,
+        Box::new(Sv443JokeProvider::with_base_url(server.uri())),
+    ] {
+        // The API's own spelling is requested whatever the caller's casing
+        for category in ["pun", "Pun", "PUN"] {
+            let joke = provider.get_joke_by_category(category).await.unwrap();
+
+            assert_twopart(&joke, "147", "What do you call a fake noodle?", "An impasta.", "pun");
+            assert_eq!(joke.safe, Some(true));
+            assert_eq!(joke.lang.as_deref(), Some("en"));
+        }
+    }
+}
+
+#[tokio::test]
+async fn jokeapi_error_payload() {
+    let server = serve("/joke/Any", 400, "jokeapi/error.json").await;
+    let error = JokesApiProvider::with_base_url(server.uri()).get_random_joke().await.unwrap_err();
+    assert_eq!(error, ProviderError::HttpStatus { status: 400, retry_after: None });
+
+    // Recognised by its body even when served with a 200
+    let server = serve("/joke/Any", 200, "jokeapi/error.json").await;
+    for provider in [
+        Box::new(JokesApiProvider::with_base_url(server.uri())) as Box<dyn JokeProvider>,
+        Box::new(Sv443JokeProvider::with_base_url(server.uri())),
+    ] {
+        assert_eq!(
+            provider.get_random_joke().await.unwrap_err(),
+            ProviderError::NoMatchingJoke(
+                "No matching joke found (No jokes were found that match your provided filter(s).)".to_string()
+            )
+        );
+    }
+
+    // Other error codes are upstream failures
+    let server = serve("/joke/Any", 200, "jokeapi/internal_error.json").await;
+    assert_eq!(
+        random_error(JokesApiProvider::with_base_url(server.uri())).await,
+        ProviderError::Upstream(
+            "Internal Error (An unhandled error occurred while trying to serve the joke.)".to_string()
+        )
+    );
+}
+
+#[test]
+fn jokeapi_does_not_list_categories_safe_mode_filters_out() {
+    let jokeapi: Box<dyn JokeProvider> = Box::new(JokesApiProvider::new());
+    for provider in [jokeapi, Box::new(Sv443JokeProvider::new())] {
+        assert!(!provider.get_supported_categories().contains(&"dark".to_string()));
+    }
+}
+
+#[tokio::test]
+async fn jokeapi_missing_fields() {
+    let server = serve("/joke/Any", 200, "jokeapi/missing_fields.json").await;
+
+    assert_eq!(
+        random_error(JokesApiProvider::with_base_url(server.uri())).await,
+        ProviderError::Decode("missing field `joke`".to_string())
+    );
+    assert_eq!(
+        random_error(Sv443JokeProvider::with_base_url(server.uri())).await,
+        ProviderError::Decode("missing field `joke`".to_string())
+    );
+}
+
+#[tokio::test]
+async fn jokeapi_unexpected_types() {
+    let server = serve("/joke/Any", 200, "jokeapi/unexpected_types.json").await;
+
+    assert_eq!(
+        random_error(JokesApiProvider::with_base_url(server.uri())).await,
+        ProviderError::Decode("category: invalid type: integer `7`, expected a string".to_string())
+    );
+}
+
+#[tokio::test]
+async fn jokeapi_unknown_type() {
+    let server = serve("/joke/Any", 200, "jokeapi/unknown_type.json").await;
+
+    assert_eq!(
+        random_error(JokesApiProvider::with_base_url(server.uri())).await,
+        ProviderError::Decode("type: unknown variant `limerick`, expected `single` or `twopart`".to_string())
+    );
+}
+
+// icanhazdadjoke
+
+#[tokio::test]
+async fn icanhazdadjoke_single() {
+    let server = serve("/", 200, "icanhazdadjoke/single.json").await;
+
+    let joke = DadJokesProvider::with_base_url(server.uri()).get_random_joke().await.unwrap();
+
+    assert_single(
+        &joke,
+        Some("R7UfaahVfFd"),
+        "My dog used to chase people on a bike a lot. It got so bad I had to take his bike away.",
+        Some("dad jokes"),
+    );
+}
+
+#[tokio::test]
+async fn icanhazdadjoke_error_payload() {
+    let server = serve("/", 404, "icanhazdadjoke/error.json").await;
+
+    let error = DadJokesProvider::with_base_url(server.uri()).get_random_joke().await.unwrap_err();
+    assert_eq!(error, ProviderError::HttpStatus { status: 404, retry_after: None });
+
+    let server = serve("/", 200, "icanhazdadjoke/error.json").await;
+    assert_eq!(
+        random_error(DadJokesProvider::with_base_url(server.uri())).await,
+        ProviderError::Upstream("Joke not found (status 404)".to_string())
+    );
+}
+
+#[tokio::test]
+async fn icanhazdadjoke_missing_fields() {
+    let server = serve("/", 200, "icanhazdadjoke/missing_fields.json").await;
+
+    assert_eq!(
+        random_error(DadJokesProvider::with_base_url(server.uri())).await,
+        ProviderError::Decode("missing field `id`".to_string())
+    );
+}
+
+#[tokio::test]
+async fn icanhazdadjoke_unexpected_types() {
+    let server = serve("/", 200, "icanhazdadjoke/unexpected_types.json").await;
+
+    assert_eq!(
+        random_error(DadJokesProvider::with_base_url(server.uri())).await,
+        ProviderError::Decode("id: invalid type: integer `42`, expected a string".to_string())
+    );
+}
+
+// Chuck Norris
+
+#[tokio::test]
+async fn chucknorris_single() {
+    let server = serve("/jokes/random", 200, "chucknorris/single.json").await;
+
+    let joke = ChuckNorrisProvider::with_base_url(server.uri()).get_joke_by_category("dev").await.unwrap();
+
+    assert_single(
+        &joke,
+        Some("elgv2wkvt8ioag6xywykbq"),
+        "Chuck Norris's keyboard doesn't have a Ctrl key because nothing controls Chuck Norris.",
+        Some("dev"),
+    );
+}
+
+#[tokio::test]
+async fn chucknorris_uncategorized() {
+    let server = serve("/jokes/random", 200, "chucknorris/uncategorized.json").await;
+
+    let joke = ChuckNorrisProvider::with_base_url(server.uri()).get_random_joke().await.unwrap();
+
+    assert_single(&joke, Some("a9Wa7MSpRlGBDNkgOz_8Ow"), "Chuck Norris can divide by zero.", Some("uncategorized"));
+}
+
+#[tokio::test]
+async fn chucknorris_error_payload() {
+    let server = serve("/jokes/random", 404, "chucknorris/error.json").await;
+
+    let error = ChuckNorrisProvider::with_base_url(server.uri()).get_joke_by_category("dev").await.unwrap_err();
+    assert_eq!(error, ProviderError::HttpStatus { status: 404, retry_after: None });
+
+    let server = serve("/jokes/random", 200, "chucknorris/error.json").await;
+    assert_eq!(
+        random_error(ChuckNorrisProvider::with_base_url(server.uri())).await,
+        ProviderError::Upstream("No jokes for category \"foo\" found.".to_string())
+    );
+}
+
+#[tokio::test]
+async fn chucknorris_missing_fields() {
+    let server = serve("/jokes/random", 200, "chucknorris/missing_fields.json").await;
+
+    assert_eq!(
+        random_error(ChuckNorrisProvider::with_base_url(server.uri())).await,
+        ProviderError::Decode("missing field `value`".to_string())
+    );
+}
+
+#[tokio::test]
+async fn chucknorris_unexpected_types() {
+    let server = serve("/jokes/random", 200, "chucknorris/unexpected_types.json").await;
+
+    assert_eq!(
+        random_error(ChuckNorrisProvider::with_base_url(server.uri())).await,
+        ProviderError::Decode("categories: invalid type: string \"dev\", expected a sequence".to_string())
+    );
+}
+
+// Official Joke API
+
+#[tokio::test]
+async fn officialjoke_twopart() {
+    let server = serve("/random_joke", 200, "officialjoke/twopart.json").await;
+
+    let joke = OfficialJokeProvider::with_base_url(server.uri()).get_random_joke().await.unwrap();
+
+    assert_twopart(&joke, "215", "What do you call a belt made of watches?", "A waist of time.", "general");
+}
+
+#[tokio::test]
+async fn officialjoke_category_array() {
+    let server = serve("/jokes/programming/random", 200, "officialjoke/category.json").await;
+
+    let joke = OfficialJokeProvider::with_base_url(server.uri())
+        .get_joke_by_category("programming")
+        .await
+        .unwrap();
+
+    assert_twopart(
+        &joke,
+        "16",
+        "How many programmers does it take to change a light bulb?",
+        "None, that's a hardware problem.",
+        "programming",
+    );
+}
+
+#[tokio::test]
+async fn officialjoke_error_payload() {
+    let server = serve("/random_joke", 404, "officialjoke/error.json").await;
+
+    let error = OfficialJokeProvider::with_base_url(server.uri()).get_random_joke().await.unwrap_err();
+    assert_eq!(error, ProviderError::HttpStatus { status: 404, retry_after: None });
+
+    let server = serve("/jokes/programming/random", 200, "officialjoke/error.json").await;
+    let error = OfficialJokeProvider::with_base_url(server.uri())
+        .get_joke_by_category("programming")
+        .await
+        .unwrap_err();
+    assert_eq!(error, ProviderError::Upstream("joke not found".to_string()));
+}
+
+#[tokio::test]
+async fn officialjoke_missing_fields() {
+    let server = serve("/random_joke", 200, "officialjoke/missing_fields.json").await;
+
+    assert_eq!(
+        random_error(OfficialJokeProvider::with_base_url(server.uri())).await,
+        ProviderError::Decode("missing field `setup`".to_string())
+    );
+}
+
+#[tokio::test]
+async fn officialjoke_unexpected_types() {
+    let server = serve("/random_joke", 200, "officialjoke/unexpected_types.json").await;
+
+    assert_eq!(
+        random_error(OfficialJokeProvider::with_base_url(server.uri())).await,
+        ProviderError::Decode("id: invalid type: string \"215\", expected u64".to_string())
+    );
+}
+
+// Jokes One
+
+#[tokio::test]
+async fn jokesone_joke_of_the_day() {
+    let server = serve("/jod", 200, "jokesone/jod.json").await;
+
+    let joke = JokesOneProvider::with_base_url(server.uri(), None).unwrap().get_random_joke().await.unwrap();
+
+    // The entry's category is the 'jod' feed rather than a joke category
+    assert_single(
+        &joke,
+        Some("lW9dqr0Qn4Cw4HNwRPGgbgeF"),
+        "Why did the scarecrow win an award? He was outstanding in his field.",
+        None,
+    );
+    assert_eq!(joke.lang.as_deref(), Some("en"));
+}
+
+#[tokio::test]
+async fn jokesone_rate_limited() {
+    let server = serve("/jod", 429, "jokesone/error.json").await;
+
+    let error = JokesOneProvider::with_base_url(server.uri(), None).unwrap().get_random_joke().await.unwrap_err();
+    assert_eq!(error, ProviderError::RateLimited { retry_after: None });
+
+    // Recognised by its body even when served with a 200
+    let server = serve("/jod", 200, "jokesone/error.json").await;
+    assert_eq!(
+        random_error(JokesOneProvider::with_base_url(server.uri(), None).unwrap()).await,
+        ProviderError::RateLimited { retry_after: None }
+    );
+}
+
+#[tokio::test]
+async fn jokesone_missing_fields() {
+    let server = serve("/jod", 200, "jokesone/missing_fields.json").await;
+
+    assert_eq!(
+        random_error(JokesOneProvider::with_base_url(server.uri(), None).unwrap()).await,
+        ProviderError::Decode("missing field `contents.jokes[0]`".to_string())
+    );
+}
+
+#[tokio::test]
+async fn jokesone_unexpected_types() {
+    let server = serve("/jod", 200, "jokesone/unexpected_types.json").await;
+
+    assert_eq!(
+        random_error(JokesOneProvider::with_base_url(server.uri(), None).unwrap()).await,
+        ProviderError::Decode("contents.jokes[0].joke.id: invalid type: integer `17`, expected a string".to_string())
+    );
+}
@@ -0,0 +1,177 @@
+//! Building providers from a TOML config, pointed at a local mock HTTP server.
+
+use std::sync::Arc;
+
+use agitated_chebyshev::lib::providers::{
+    HttpClient, JokeManager, JokeProvider, JokesApiProvider, ProviderError, ProviderRegistry, ProvidersConfig,
+    PROVIDER_IDS,
+};
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Only `enabled` is configured with `settings`; every other provider is disabled
+fn config(enabled: &str, settings: &str) -> ProvidersConfig {
+    let mut toml = String::new();
+    for id in PROVIDER_IDS {
+        if id == enabled {
+            toml.push_str(&format!("[providers.{}]\n{}\n", id, settings));
+        } else {
+            toml.push_str(&format!("[providers.{}]\nenabled = false\n", id));
+        }
+    }
+    ProvidersConfig::from_toml(&toml).unwrap()
+}
+
+#[tokio::test]
+async fn providers_use_the_configured_base_url_and_user_agent() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/jokes/random"))
+        .and(header("User-Agent", "jokes-test/1.0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "abc",
+            "value": "Chuck Norris counted to infinity. Twice.",
+            "categories": []
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let config = config(
+        "chucknorris",
+        &format!("base_url = \"{}/\"\nuser_agent = \"jokes-test/1.0\"\ntimeout_secs = 5", server.uri()),
+    );
+    let registry = ProviderRegistry::from_config(&config, &HttpClient::default()).unwrap();
+    assert_eq!(registry.providers().len(), 1);
+
+    let served = JokeManager::from_registry(&registry).get_random_joke().await.unwrap();
+
+    // Stored under the provider id, whatever the base URL
+    assert_eq!(served.joke.provider, "chucknorris");
+    assert_eq!(served.joke.joke.joke.content.as_deref(), Some("Chuck Norris counted to infinity. Twice."));
+}
+
+#[tokio::test]
+async fn self_hosted_base_url_keeps_the_provider_id() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/joke/Any"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "type": "single",
+            "id": 7,
+            "joke": "Self-hosted joke",
+            "category": "Programming",
+            "safe": true,
+            "lang": "en"
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider: Arc<dyn JokeProvider> = Arc::new(JokesApiProvider::with_base_url(server.uri()));
+    assert_eq!(provider.base_url(), server.uri());
+
+    let manager = JokeManager::new(vec![provider]);
+    let served = manager.get_random_joke().await.unwrap();
+
+    assert_eq!(served.joke.provider, "jokeapi");
+    assert_eq!(manager.get_health()[0].provider, "jokeapi");
+}
+
+#[tokio::test]
+async fn providers_are_looked_up_by_exact_id() {
+    let manager = JokeManager::with_all_providers();
+
+    assert_eq!(manager.provider("sv443").map(|provider| provider.id()), Some("sv443"));
+    assert!(manager.provider("jokeapi").is_some());
+    assert!(manager.provider("joke").is_none());
+    assert!(manager.provider("JokesAPI (jokeapi.dev)").is_none());
+    assert_eq!(
+        manager.get_joke_from_provider("jokes").await.unwrap_err(),
+        ProviderError::UnknownProvider("jokes".to_string())
+    );
+}
+
+#[tokio::test]
+async fn jokes_one_sends_the_configured_api_key() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/jod"))
+        .and(header("X-JokesOne-Api-Secret", "s3cret"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "contents": { "jokes": [{ "joke": { "text": "Joke of the day" } }] }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let config = config("jokesone", &format!("base_url = \"{}\"\napi_key = \"s3cret\"", server.uri()));
+    let registry = ProviderRegistry::from_config(&config, &HttpClient::default()).unwrap();
+
+    let served = JokeManager::from_registry(&registry).get_random_joke().await.unwrap();
+
+    assert_eq!(served.joke.joke.joke.content.as_deref(), Some("Joke of the day"));
+}
+
+#[tokio::test]
+async fn lists_disabled_providers_and_recent_success_rate() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/jokes/random"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "abc",
+            "value": "Chuck Norris can divide by zero.",
+            "categories": ["dev"]
+        })))
+        .mount(&server)
+        .await;
+    let config = config("chucknorris", &format!("base_url = \"{}\"", server.uri()));
+    let manager = JokeManager::from_registry(&ProviderRegistry::from_config(&config, &HttpClient::default()).unwrap());
+
+    let before = manager.get_providers();
+    manager.get_random_joke().await.unwrap();
+    let after = manager.get_providers();
+
+    assert_eq!(before.len(), 6);
+    assert_eq!(before[0].id, "chucknorris");
+    assert_eq!(before[0].base_url, server.uri());
+    assert!(before[0].enabled);
+    assert_eq!(before[0].success_rate, None);
+    assert_eq!(after[0].success_rate, Some(1.0));
+    assert!(before[1..].iter().all(|provider| !provider.enabled && provider.success_rate.is_none()));
+    assert_eq!(manager.get_all_categories()[..2], ["animal", "career"]);
+    assert!(manager.get_all_categories().contains(&"programming".to_string()));
+}
+
+#[test]
+fn weights_are_keyed_by_provider_id() {
+    let config = config("officialjoke", "weight = 4");
+    let registry = ProviderRegistry::from_config(&config, &HttpClient::default()).unwrap();
+
+    assert_eq!(registry.weights().get("officialjoke"), Some(&4));
+}
+
+#[test]
+fn rejects_unknown_providers_and_settings() {
+    assert!(ProvidersConfig::from_toml("[providers.knock_knock]\nenabled = true").is_err());
+    assert!(ProvidersConfig::from_toml("[providers.chuck_norris]\nenabled = true").is_err());
+    assert!(ProvidersConfig::from_toml("[providers.chucknorris]\nbase = \"http://localhost\"").is_err());
+}
+
+#[test]
+fn providers_are_configured_by_their_id() {
+    let registry = ProviderRegistry::from_config(&ProvidersConfig::default(), &HttpClient::default()).unwrap();
+
+    let ids: Vec<_> = registry.providers().iter().map(|provider| provider.id()).collect();
+    assert_eq!(ids, PROVIDER_IDS);
+}
+
+#[test]
+fn rejects_a_jokes_one_api_key_that_cannot_be_sent_as_a_header() {
+    let config = config("jokesone", "api_key = \"s3cret\\n\"");
+
+    let error = ProviderRegistry::from_config(&config, &HttpClient::default()).err().unwrap();
+
+    assert!(error.contains("Invalid Jokes One API key"));
+    assert!(!error.contains("s3cret"));
+}
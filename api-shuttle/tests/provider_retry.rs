@@ -0,0 +1,173 @@
+//! Retry policy tests against a local mock HTTP server.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use agitated_chebyshev::lib::providers::{
+    Joke, JokeContent, JokeProvider, JokeType, ProviderError, RetryPolicy, RetryingProvider,
+};
+use async_trait::async_trait;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Minimal provider talking to the mock server the way the real providers do
+struct MockProvider {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl MockProvider {
+    async fn fetch(&self, url: String) -> Result<Joke, ProviderError> {
+        let response = ProviderError::check_status(self.client.get(url).send().await?)?;
+        let data: serde_json::Value = response.json().await?;
+        Ok(Joke {
+            id: None,
+            joke: JokeContent {
+                content: data["joke"].as_str().map(|s| s.to_string()),
+                setup: None,
+                punchline: None,
+            },
+            category: None,
+            r#type: JokeType::Single,
+            safe: None,
+            lang: None,
+        })
+    }
+}
+
+#[async_trait]
+impl JokeProvider for MockProvider {
+    fn id(&self) -> &str {
+        "mock"
+    }
+
+    fn name(&self) -> &str {
+        "Mock"
+    }
+
+    fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    async fn get_random_joke(&self) -> Result<Joke, ProviderError> {
+        self.fetch(format!("{}/joke", self.base_url)).await
+    }
+
+    async fn get_joke_by_category(&self, category: &str) -> Result<Joke, ProviderError> {
+        self.fetch(format!("{}/joke/{}", self.base_url, category)).await
+    }
+}
+
+fn retrying(server: &MockServer, max_attempts: u32) -> RetryingProvider {
+    let provider = MockProvider {
+        client: reqwest::Client::new(),
+        base_url: server.uri(),
+    };
+    let policy = RetryPolicy {
+        max_attempts,
+        base_delay: Duration::from_millis(1),
+        max_delay: Duration::from_secs(2),
+    };
+    RetryingProvider::new(Arc::new(provider), policy)
+}
+
+fn joke() -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(serde_json::json!({ "joke": "Knock knock" }))
+}
+
+#[tokio::test]
+async fn retries_server_errors_until_success() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET")).and(path("/joke"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .expect(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET")).and(path("/joke"))
+        .respond_with(joke())
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let joke = retrying(&server, 3).get_random_joke().await.unwrap();
+
+    assert_eq!(joke.joke.content.as_deref(), Some("Knock knock"));
+}
+
+#[tokio::test]
+async fn gives_up_after_max_attempts() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET")).and(path("/joke"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    let error = retrying(&server, 3).get_random_joke().await.unwrap_err();
+
+    assert_eq!(error.status(), Some(500));
+}
+
+#[tokio::test]
+async fn does_not_retry_client_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET")).and(path("/joke/missing"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let error = retrying(&server, 3).get_joke_by_category("missing").await.unwrap_err();
+
+    assert_eq!(error, ProviderError::HttpStatus { status: 404, retry_after: None });
+}
+
+#[tokio::test]
+async fn does_not_retry_undecodable_bodies() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET")).and(path("/joke"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("not json"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let error = retrying(&server, 3).get_random_joke().await.unwrap_err();
+
+    assert!(matches!(error, ProviderError::Decode(_)));
+}
+
+#[tokio::test]
+async fn honours_retry_after_on_rate_limit() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET")).and(path("/joke/pun"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "1"))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET")).and(path("/joke/pun"))
+        .respond_with(joke())
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let started = Instant::now();
+    retrying(&server, 3).get_joke_by_category("pun").await.unwrap();
+
+    assert!(started.elapsed() >= Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn gives_up_when_retry_after_exceeds_max_delay() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET")).and(path("/joke"))
+        .respond_with(ResponseTemplate::new(503).insert_header("Retry-After", "60"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let error = retrying(&server, 3).get_random_joke().await.unwrap_err();
+
+    assert_eq!(error.retry_after(), Some(Duration::from_secs(60)));
+}
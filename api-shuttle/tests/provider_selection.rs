@@ -0,0 +1,103 @@
+//! Provider selection strategies.
+
+mod common;
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use agitated_chebyshev::lib::providers::{JokeProvider, JokeType, ProviderSelector, SelectionStrategy};
+use common::StubProvider;
+
+fn providers() -> Vec<Arc<dyn JokeProvider>> {
+    vec![
+        Arc::new(StubProvider::working("a")),
+        Arc::new(StubProvider::working("b").with_joke_types(&[JokeType::Single, JokeType::Twopart])),
+        Arc::new(StubProvider::working("c")),
+    ]
+}
+
+fn choices(selector: &ProviderSelector, latencies: &[Option<u64>], rounds: usize) -> Vec<&'static str> {
+    let providers = providers();
+    let candidates: Vec<_> = providers.iter().collect();
+    let names = ["a", "b", "c"];
+    (0..rounds)
+        .map(|_| {
+            let index = selector.choose(&candidates, |provider| {
+                latencies[names.iter().position(|name| *name == provider.name()).unwrap()]
+            });
+            names[index]
+        })
+        .collect()
+}
+
+#[test]
+fn round_robin_takes_turns() {
+    let selector = ProviderSelector::new(SelectionStrategy::RoundRobin);
+
+    assert_eq!(choices(&selector, &[None; 3], 4), vec!["a", "b", "c", "a"]);
+}
+
+#[test]
+fn weighted_never_picks_zero_weight_providers() {
+    let weights = HashMap::from([("a".to_string(), 0), ("c".to_string(), 0)]);
+    let selector = ProviderSelector::new(SelectionStrategy::Weighted(weights));
+
+    assert!(choices(&selector, &[None; 3], 50).iter().all(|name| *name == "b"));
+}
+
+#[test]
+fn weighted_picks_uniformly_when_every_provider_weighs_zero() {
+    let weights = HashMap::from([("a".to_string(), 0), ("b".to_string(), 0), ("c".to_string(), 0)]);
+    let selector = ProviderSelector::new(SelectionStrategy::Weighted(weights));
+
+    let picked: HashSet<_> = choices(&selector, &[None; 3], 100).into_iter().collect();
+
+    assert_eq!(picked.len(), 3);
+}
+
+#[test]
+fn least_latency_spreads_calls_over_unmeasured_providers() {
+    let selector = ProviderSelector::new(SelectionStrategy::LeastLatency);
+
+    let mut picked = choices(&selector, &[Some(10), None, None], 100);
+    picked.sort();
+    picked.dedup();
+
+    assert_eq!(picked, vec!["b", "c"]);
+}
+
+#[test]
+fn least_latency_spreads_calls_over_the_fastest_tier() {
+    let selector = ProviderSelector::new(SelectionStrategy::LeastLatency);
+
+    let mut picked = choices(&selector, &[Some(100), Some(10), Some(12)], 100);
+    picked.sort();
+    picked.dedup();
+
+    assert_eq!(picked, vec!["b", "c"]);
+    assert_eq!(choices(&selector, &[Some(30), Some(20), Some(10)], 20), vec!["c"; 20]);
+}
+
+#[tokio::test]
+async fn least_latency_spreads_a_cold_start_batch() {
+    let providers = ["a", "b", "c"].map(StubProvider::working).into();
+    let manager = common::manager(providers, "fail").with_selection_strategy(SelectionStrategy::LeastLatency);
+
+    let batch = manager.get_multiple_jokes(30).await.unwrap();
+
+    let used: HashSet<_> = batch.jokes.iter().map(|joke| joke.provider.as_str()).collect();
+    assert_eq!(used.len(), 3);
+}
+
+#[test]
+fn prefer_twopart_picks_twopart_providers() {
+    let selector = ProviderSelector::new(SelectionStrategy::PreferTwopart);
+
+    assert!(choices(&selector, &[None; 3], 50).iter().all(|name| *name == "b"));
+}
+
+#[test]
+fn parse_rejects_unknown_strategies() {
+    assert!(matches!(SelectionStrategy::parse("round-robin", HashMap::new()), Ok(SelectionStrategy::RoundRobin)));
+    assert!(SelectionStrategy::parse("fastest", HashMap::new()).is_err());
+}
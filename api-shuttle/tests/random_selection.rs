@@ -0,0 +1,97 @@
+//! Benchmark of random joke selection against a seeded local Postgres.
+//!
+//! Run with `DATABASE_URL=postgresql://... cargo test --test random_selection -- --ignored --nocapture`.
+//! The test creates and drops its own scratch database next to the one in `DATABASE_URL`.
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use agitated_chebyshev::db::filters::JokeFilters;
+use agitated_chebyshev::db::jokes;
+use common::ScratchDatabase;
+use sqlx::types::Uuid;
+
+const SEED_ROWS: i64 = 200_000;
+const ITERATIONS: u32 = 50;
+
+async fn time_it<F, Fut>(mut f: F) -> Duration
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Option<Uuid>>,
+{
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        assert!(f().await.is_some(), "seeded table should always yield a joke");
+    }
+    start.elapsed() / ITERATIONS
+}
+
+#[tokio::test]
+#[ignore = "requires a local Postgres via DATABASE_URL"]
+async fn random_key_selection_beats_order_by_random() {
+    let db = ScratchDatabase::create("jokes_bench").await;
+    let pool = db.pool.clone();
+
+    sqlx::query(
+        r#"
+        INSERT INTO jokes (external_id, joke, category, type, provider)
+        SELECT i::text,
+            jsonb_build_object('content', 'Benchmark joke ' || i),
+            (ARRAY['programming', 'pun', 'dad'])[1 + i % 3],
+            'single',
+            'bench'
+        FROM generate_series(1, $1) AS i
+        "#,
+    )
+    .bind(SEED_ROWS)
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query("ANALYZE jokes").execute(&pool).await.unwrap();
+
+    let unfiltered = JokeFilters::default();
+    let filtered = JokeFilters {
+        category: Some("programming".to_string()),
+        ..JokeFilters::default()
+    };
+
+    let order_by_random = time_it(|| async {
+        sqlx::query_as::<_, (Uuid,)>("SELECT id FROM jokes ORDER BY RANDOM() LIMIT 1")
+            .fetch_optional(&pool)
+            .await
+            .unwrap()
+            .map(|(id,)| id)
+    })
+    .await;
+    let order_by_random_filtered = time_it(|| async {
+        sqlx::query_as::<_, (Uuid,)>(
+            "SELECT id FROM jokes WHERE category = 'programming' ORDER BY RANDOM() LIMIT 1",
+        )
+        .fetch_optional(&pool)
+        .await
+        .unwrap()
+        .map(|(id,)| id)
+    })
+    .await;
+    let random_key = time_it(|| async {
+        jokes::random(&pool, &unfiltered).await.unwrap().map(|joke| joke.id)
+    })
+    .await;
+    let random_key_filtered = time_it(|| async {
+        jokes::random(&pool, &filtered).await.unwrap().map(|joke| joke.id)
+    })
+    .await;
+
+    println!("{} rows, mean over {} picks:", SEED_ROWS, ITERATIONS);
+    println!("  ORDER BY RANDOM()              {:?}", order_by_random);
+    println!("  random_key                     {:?}", random_key);
+    println!("  ORDER BY RANDOM() (category)   {:?}", order_by_random_filtered);
+    println!("  random_key (category)          {:?}", random_key_filtered);
+
+    drop(pool);
+    db.drop().await;
+
+    assert!(random_key < order_by_random);
+    assert!(random_key_filtered < order_by_random_filtered);
+}